@@ -0,0 +1,333 @@
+// Action stuff
+//
+// Everything that changes the app goes through `App::dispatch` with one of
+// these actions, whether it comes from a key binding, the CLI or a test.
+// Actions without data can be bound to keys by name from the config file.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Help,
+    PreviousProject,
+    NextProject,
+    SelectProject(u32),
+    PreviousTask,
+    NextTask,
+    FirstTask,
+    LastTask,
+    PageUp,
+    PageDown,
+    SelectTask(u32),
+    ToggleTask,
+    CompleteTask,
+    CancelTask,
+    MoveTaskLeft,
+    MoveTaskRight,
+    AddTask,
+    CreateTask(String),
+    RenameTask,
+    EditTask,
+    ApplyEdit(String),
+    DeleteTask,
+    ToggleMark,
+    MarkRange,
+    MarkAll,
+    MarkSameState,
+    ClearMarks,
+    MoveTasks,
+    TagTasks,
+    Undo,
+    NewProject,
+    RenameProject,
+    ToggleSidebar,
+    ToggleBoard,
+    PreviousColumn,
+    NextColumn,
+    CycleSort,
+    ReverseSort,
+    ToggleGrouping,
+    CycleHidden,
+    ArchiveDone,
+    ShowArchive,
+    RestoreTask,
+    ToggleDates,
+    SwitchFocus,
+    Filter,
+    ClearFilter,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Palette,
+    ExportOrg,
+    ExportReport,
+    Validate,
+    Abort,
+    InsertChar(char),
+    Paste(String),
+    DeleteChar,
+    DeleteNextChar,
+    DeleteWordLeft,
+    DeleteWordRight,
+    KillToStart,
+    KillToEnd,
+    Yank,
+    CursorLeft,
+    CursorRight,
+    WordLeft,
+    WordRight,
+    CursorStart,
+    CursorEnd,
+    PreviousHistory,
+    NextHistory,
+    Complete,
+    PreviousItem,
+    NextItem,
+    Confirm,
+}
+
+impl Action {
+    pub const BINDABLE: &'static [Action] = &[
+        Action::Quit,
+        Action::Help,
+        Action::PreviousProject,
+        Action::NextProject,
+        Action::PreviousTask,
+        Action::NextTask,
+        Action::FirstTask,
+        Action::LastTask,
+        Action::PageUp,
+        Action::PageDown,
+        Action::ToggleTask,
+        Action::CompleteTask,
+        Action::CancelTask,
+        Action::MoveTaskLeft,
+        Action::MoveTaskRight,
+        Action::AddTask,
+        Action::RenameTask,
+        Action::EditTask,
+        Action::DeleteTask,
+        Action::ToggleMark,
+        Action::MarkRange,
+        Action::MarkAll,
+        Action::MarkSameState,
+        Action::ClearMarks,
+        Action::MoveTasks,
+        Action::TagTasks,
+        Action::Undo,
+        Action::NewProject,
+        Action::RenameProject,
+        Action::ToggleSidebar,
+        Action::ToggleBoard,
+        Action::PreviousColumn,
+        Action::NextColumn,
+        Action::CycleSort,
+        Action::ReverseSort,
+        Action::ToggleGrouping,
+        Action::CycleHidden,
+        Action::ArchiveDone,
+        Action::ShowArchive,
+        Action::RestoreTask,
+        Action::ToggleDates,
+        Action::SwitchFocus,
+        Action::Filter,
+        Action::ClearFilter,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::Palette,
+        Action::ExportOrg,
+        Action::ExportReport,
+        Action::Validate,
+        Action::Abort,
+        Action::DeleteChar,
+        Action::DeleteNextChar,
+        Action::DeleteWordLeft,
+        Action::DeleteWordRight,
+        Action::KillToStart,
+        Action::KillToEnd,
+        Action::Yank,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::WordLeft,
+        Action::WordRight,
+        Action::CursorStart,
+        Action::CursorEnd,
+        Action::PreviousHistory,
+        Action::NextHistory,
+        Action::Complete,
+        Action::PreviousItem,
+        Action::NextItem,
+        Action::Confirm,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::PreviousProject => "previous_project",
+            Action::NextProject => "next_project",
+            Action::SelectProject(_) => "select_project",
+            Action::PreviousTask => "previous_task",
+            Action::NextTask => "next_task",
+            Action::FirstTask => "first_task",
+            Action::LastTask => "last_task",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::SelectTask(_) => "select_task",
+            Action::ToggleTask => "toggle_task",
+            Action::CompleteTask => "complete_task",
+            Action::CancelTask => "cancel_task",
+            Action::MoveTaskLeft => "move_task_left",
+            Action::MoveTaskRight => "move_task_right",
+            Action::AddTask => "add_task",
+            Action::CreateTask(_) => "create_task",
+            Action::RenameTask => "rename_task",
+            Action::EditTask => "edit_task",
+            Action::ApplyEdit(_) => "apply_edit",
+            Action::DeleteTask => "delete_task",
+            Action::ToggleMark => "toggle_mark",
+            Action::MarkRange => "mark_range",
+            Action::MarkAll => "mark_all",
+            Action::MarkSameState => "mark_same_state",
+            Action::ClearMarks => "clear_marks",
+            Action::MoveTasks => "move_tasks",
+            Action::TagTasks => "tag_tasks",
+            Action::Undo => "undo",
+            Action::NewProject => "new_project",
+            Action::RenameProject => "rename_project",
+            Action::ToggleSidebar => "toggle_sidebar",
+            Action::ToggleBoard => "toggle_board",
+            Action::PreviousColumn => "previous_column",
+            Action::NextColumn => "next_column",
+            Action::CycleSort => "cycle_sort",
+            Action::ReverseSort => "reverse_sort",
+            Action::ToggleGrouping => "toggle_grouping",
+            Action::CycleHidden => "cycle_hidden",
+            Action::ArchiveDone => "archive_done",
+            Action::ShowArchive => "show_archive",
+            Action::RestoreTask => "restore_task",
+            Action::ToggleDates => "toggle_dates",
+            Action::SwitchFocus => "switch_focus",
+            Action::Filter => "filter",
+            Action::ClearFilter => "clear_filter",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::Palette => "palette",
+            Action::ExportOrg => "export_org",
+            Action::ExportReport => "export_report",
+            Action::Validate => "validate",
+            Action::Abort => "abort",
+            Action::InsertChar(_) => "insert_char",
+            Action::Paste(_) => "paste",
+            Action::DeleteChar => "delete_char",
+            Action::DeleteNextChar => "delete_next_char",
+            Action::DeleteWordLeft => "delete_word_left",
+            Action::DeleteWordRight => "delete_word_right",
+            Action::KillToStart => "kill_to_start",
+            Action::KillToEnd => "kill_to_end",
+            Action::Yank => "yank",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::WordLeft => "word_left",
+            Action::WordRight => "word_right",
+            Action::CursorStart => "cursor_start",
+            Action::CursorEnd => "cursor_end",
+            Action::PreviousHistory => "previous_history",
+            Action::NextHistory => "next_history",
+            Action::Complete => "complete",
+            Action::PreviousItem => "previous_item",
+            Action::NextItem => "next_item",
+            Action::Confirm => "confirm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::BINDABLE
+            .iter()
+            .find(|a| a.get_name() == name)
+            .cloned()
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Save and quit",
+            Action::Help => "Show this help",
+            Action::PreviousProject => "Previous project",
+            Action::NextProject => "Next project",
+            Action::SelectProject(_) => "Switch to project",
+            Action::PreviousTask => "Previous task",
+            Action::NextTask => "Next task",
+            Action::FirstTask => "First task",
+            Action::LastTask => "Last task",
+            Action::PageUp => "One page up",
+            Action::PageDown => "One page down",
+            Action::SelectTask(_) => "Select task",
+            Action::ToggleTask => "Toggle task completed",
+            Action::CompleteTask => "Complete task",
+            Action::CancelTask => "Cancel task",
+            Action::MoveTaskLeft => "Move card to the previous column",
+            Action::MoveTaskRight => "Move card to the next column",
+            Action::AddTask => "Add a task",
+            Action::CreateTask(_) => "Create task",
+            Action::RenameTask => "Rename task",
+            Action::EditTask => "Edit task in $EDITOR",
+            Action::ApplyEdit(_) => "Apply edited task",
+            Action::DeleteTask => "Delete task",
+            Action::ToggleMark => "Mark or unmark task",
+            Action::MarkRange => "Mark up to the last marked task",
+            Action::MarkAll => "Mark or unmark every task",
+            Action::MarkSameState => "Mark tasks in the same state",
+            Action::ClearMarks => "Unmark every task",
+            Action::MoveTasks => "Move tasks to another project",
+            Action::TagTasks => "Tag tasks",
+            Action::Undo => "Undo last change",
+            Action::NewProject => "New project",
+            Action::RenameProject => "Rename project",
+            Action::ToggleSidebar => "Show or hide the project list",
+            Action::ToggleBoard => "Switch between list and board",
+            Action::PreviousColumn => "Previous column",
+            Action::NextColumn => "Next column",
+            Action::CycleSort => "Sort by the next key",
+            Action::ReverseSort => "Reverse the sort order",
+            Action::ToggleGrouping => "Group tasks by state",
+            Action::CycleHidden => "Hide completed or canceled tasks",
+            Action::ArchiveDone => "Archive finished tasks",
+            Action::ShowArchive => "Show archived tasks",
+            Action::RestoreTask => "Restore archived task",
+            Action::ToggleDates => "Show or hide task dates",
+            Action::SwitchFocus => "Switch between projects and tasks",
+            Action::Filter => "Filter tasks",
+            Action::ClearFilter => "Clear filter",
+            Action::Search => "Search every project",
+            Action::NextMatch => "Next search match",
+            Action::PreviousMatch => "Previous search match",
+            Action::Palette => "Command palette",
+            Action::ExportOrg => "Export to org file",
+            Action::ExportReport => "Export HTML report",
+            Action::Validate => "Validate",
+            Action::Abort => "Cancel",
+            Action::InsertChar(_) => "Type text",
+            Action::Paste(_) => "Paste text",
+            Action::DeleteChar => "Delete previous character",
+            Action::DeleteNextChar => "Delete next character",
+            Action::DeleteWordLeft => "Delete previous word",
+            Action::DeleteWordRight => "Delete next word",
+            Action::KillToStart => "Delete to start of line",
+            Action::KillToEnd => "Delete to end of line",
+            Action::Yank => "Paste deleted text",
+            Action::CursorLeft => "Move cursor left",
+            Action::CursorRight => "Move cursor right",
+            Action::WordLeft => "Move to previous word",
+            Action::WordRight => "Move to next word",
+            Action::CursorStart => "Move to start of line",
+            Action::CursorEnd => "Move to end of line",
+            Action::PreviousHistory => "Previous entered task",
+            Action::NextHistory => "Next entered task",
+            Action::Complete => "Complete word",
+            Action::PreviousItem => "Previous entry",
+            Action::NextItem => "Next entry",
+            Action::Confirm => "Confirm",
+        }
+    }
+}
@@ -1,6 +1,25 @@
-use std::{error::Error, vec};
+use ratatui::widgets::ListState;
+use std::{collections::BTreeSet, error::Error, fs, time::Instant, vec};
 
-use crate::comps::{CursorManager, FileManager, Project, Task};
+use crate::{
+    action::Action,
+    archive::{self, Archive},
+    batch::{self, Batch, BatchOperation},
+    comps::{CursorDirection, CursorManager, FileManager, HideMode, Project, Task, TaskState},
+    date, editor,
+    history::History,
+    keymap::{KeyContext, KeyPress, Keymap},
+    mouse::{HitArea, Target},
+    org, palette,
+    query::{Query, QueryError},
+    report, search,
+    theme::Theme,
+    vi::{Vi, ViState},
+};
+
+const MAX_UNDO_STEPS: usize = 100;
+pub const EXPORT_ORG_EXTENSION: &str = "org";
+pub const EXPORT_REPORT_EXTENSION: &str = "report.html";
 
 // App stuff
 
@@ -12,14 +31,49 @@ pub enum SwitchProjectsDirection {
 pub enum TaskNavDirection {
     Up,
     Down,
+    First,
+    Last,
+    PageUp,
+    PageDown,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScreenMode {
     Main,
+    Board,
+    Sidebar,
     AddingTask,
     RenamingTask,
     RenamingProject,
     DeletingTask,
+    MovingTasks,
+    TaggingTasks,
+    ConfirmingBatch,
+    Filtering,
+    Searching,
+    Palette,
+    Archive,
+}
+
+impl ScreenMode {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ScreenMode::Main => "Tasks",
+            ScreenMode::Board => "Board",
+            ScreenMode::Sidebar => "Projects",
+            ScreenMode::AddingTask => "Adding task",
+            ScreenMode::RenamingTask => "Renaming task",
+            ScreenMode::RenamingProject => "Renaming project",
+            ScreenMode::DeletingTask => "Deleting task",
+            ScreenMode::MovingTasks => "Moving tasks",
+            ScreenMode::TaggingTasks => "Tagging tasks",
+            ScreenMode::ConfirmingBatch => "Confirm",
+            ScreenMode::Filtering => "Filtering",
+            ScreenMode::Searching => "Searching",
+            ScreenMode::Palette => "Command palette",
+            ScreenMode::Archive => "Archive",
+        }
+    }
 }
 
 pub struct App {
@@ -30,29 +84,151 @@ pub struct App {
     pub current_task_id: u32,
     pub screen_mode: ScreenMode,
     pub cursor_manager: CursorManager,
+    pub task_list_state: ListState,
+    pub page_size: usize,
+    pub show_sidebar: bool,
+    pub board: bool,
+    // Creation or completion date after each title of the list.
+    pub show_dates: bool,
+    // For the projects that did not pick their own `HideMode`.
+    pub hide_default: HideMode,
+    pub hit_areas: Vec<HitArea>,
+    pub last_click: Option<(Instant, Target)>,
+    pub project_list_state: ListState,
+    pub filter: Option<Query>,
+    pub filter_string: String,
+    pub filter_error: Option<String>,
+    pub search: String,
+    pub search_origin: (u32, u32),
+    pub show_help: bool,
+    // First help line shown, kept in range by the ui.
+    pub help_offset: usize,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub pending_keys: Vec<KeyPress>,
+    pub should_quit: bool,
+    pub palette_index: usize,
+    pub vi: Option<Vi>,
+    // Set by `EditTask`, `run_app` opens it in the external editor.
+    pub editor_document: Option<String>,
+    // Edited document that failed to parse, with the id of its task.
+    pub editor_draft: Option<(u32, String)>,
+    pub history: History,
+    // (project id, task id) of the marked tasks.
+    pub marked: BTreeSet<(u32, u32)>,
+    // Last task marked with `ToggleMark`, where `MarkRange` starts.
+    pub mark_anchor: Option<u32>,
+    // Batch waiting for confirmation.
+    pub batch: Option<Batch>,
+    // Snapshots of the projects and the archive, the latest last.
+    pub undo_stack: Vec<(Vec<Project>, Archive)>,
+    pub archive: Archive,
+    // Finished tasks older than this are archived at startup.
+    pub archive_after_days: Option<u64>,
+    // Selected entry of the archive view.
+    pub archive_index: usize,
+    pub message: Option<String>,
 }
 
 impl App {
     pub fn build() -> Result<Self, Box<dyn Error>> {
-        let mut app = App {
+        App::from_file_manager(FileManager::build()?)
+    }
+
+    fn new(file_manager: FileManager) -> Self {
+        App {
             projects: vec![],
-            file_manager: FileManager::build().unwrap(),
+            file_manager,
             next_task_id: 0,
             current_project_id: 0,
             current_task_id: 0,
             screen_mode: ScreenMode::Main,
             cursor_manager: CursorManager::new(),
-        };
+            task_list_state: ListState::default(),
+            page_size: 10,
+            show_sidebar: true,
+            board: false,
+            show_dates: false,
+            hide_default: HideMode::None,
+            hit_areas: vec![],
+            last_click: None,
+            project_list_state: ListState::default(),
+            filter: None,
+            filter_string: String::new(),
+            filter_error: None,
+            search: String::new(),
+            search_origin: (0, 0),
+            show_help: false,
+            help_offset: 0,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            pending_keys: vec![],
+            should_quit: false,
+            palette_index: 0,
+            vi: None,
+            editor_document: None,
+            editor_draft: None,
+            history: History::new(vec![]),
+            marked: BTreeSet::new(),
+            mark_anchor: None,
+            batch: None,
+            undo_stack: vec![],
+            archive: Archive::default(),
+            archive_after_days: None,
+            archive_index: 0,
+            message: None,
+        }
+    }
+
+    pub fn from_file_manager(file_manager: FileManager) -> Result<Self, Box<dyn Error>> {
+        let mut app = App::new(file_manager);
         app.read_file()?;
-        app.init_next_task_id();
-        app.get_current_project_id();
-        app.init_current_task_id();
+        app.read_stores()?;
+        Ok(app)
+    }
+
+    // For the command line: nothing is written and no demo data is created,
+    // so a data file that fails to parse is reported instead of replaced.
+    // Commands that change the tasks save them themselves.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        App::load_from(FileManager::locate()?)
+    }
+
+    pub fn load_from(file_manager: FileManager) -> Result<Self, Box<dyn Error>> {
+        let mut app = App::new(file_manager);
+        app.projects = app.file_manager.read_projects()?;
+        app.backfill_timestamps();
+        app.read_stores()?;
         Ok(app)
     }
 
+    // History and archive, once the projects are read.
+    fn read_stores(&mut self) -> Result<(), Box<dyn Error>> {
+        self.history = History::new(self.file_manager.open_history()?);
+        self.archive = self.file_manager.open_archive()?;
+        for entry in &mut self.archive.entries {
+            entry.task.backfill(entry.archived_at);
+        }
+        self.init_next_task_id();
+        self.get_current_project_id();
+        self.init_current_task_id();
+        Ok(())
+    }
+
+    fn backfill_timestamps(&mut self) {
+        let modified = self
+            .file_manager
+            .get_modified_time()
+            .unwrap_or_else(date::now);
+        for task in self.projects.iter_mut().flat_map(|p| p.tasks.iter_mut()) {
+            task.backfill(modified);
+        }
+    }
+
     pub fn read_file(&mut self) -> Result<(), Box<dyn Error>> {
         self.projects = self.file_manager.open_file()?;
-        if self.projects.len() == 0 {
+        self.backfill_timestamps();
+        if self.projects.is_empty() {
             self.projects.push(Project::new(1, String::from("Welcome")));
             self.projects[0].add_task(1, String::from("Welcome in tuido"));
             self.projects[0].add_task(2, String::from("You'll love tuido"));
@@ -70,12 +246,19 @@ impl App {
         Ok(())
     }
 
+    pub fn save_archive(&mut self) -> Result<(), Box<dyn Error>> {
+        self.file_manager.save_archive(&self.archive)?;
+        Ok(())
+    }
+
+    // Archived tasks keep their id, so new ones start after them too.
     pub fn init_next_task_id(&mut self) {
         if let Some(next_task_id) = self
             .projects
             .iter()
             .flat_map(|proj| proj.tasks.iter())
             .map(|task| task.id)
+            .chain(self.archive.get_max_id())
             .max()
         {
             self.next_task_id = next_task_id + 1;
@@ -84,7 +267,14 @@ impl App {
         }
     }
 
+    // Files written by the command line or an import may have no current
+    // project: the first one is used then.
     pub fn get_current_project_id(&mut self) {
+        if !self.projects.iter().any(|p| p.is_current) {
+            if let Some(project) = self.projects.first_mut() {
+                project.is_current = true;
+            }
+        }
         for project in &self.projects {
             if project.is_current {
                 self.current_project_id = project.id;
@@ -93,63 +283,291 @@ impl App {
         }
     }
 
-    pub fn init_current_task_id(&mut self) {
-        let ids: Vec<u32> = self
-            .projects
-            .iter()
-            .find(|proj| proj.is_current)
-            .unwrap()
-            .tasks
+    // Falls back to the first project rather than panicking on a file where
+    // none is flagged as current.
+    pub fn get_current_project(&self) -> Option<&Project> {
+        self.projects
             .iter()
+            .find(|p| p.is_current)
+            .or(self.projects.first())
+    }
+
+    pub fn get_visible_task_ids(&self) -> Vec<u32> {
+        let Some(project) = self.get_current_project() else {
+            return vec![];
+        };
+        project
+            .get_sorted_tasks()
+            .into_iter()
+            .filter(|task| self.is_task_visible(project, task))
             .map(|t| t.id)
-            .collect();
-        if let Some(id) = ids.iter().min() {
-            self.current_task_id = id.clone();
-        } else {
-            self.current_task_id = 0;
+            .collect()
+    }
+
+    pub fn is_task_visible(&self, project: &Project, task: &Task) -> bool {
+        if self.get_hide_mode(project).hides(task.state) {
+            return false;
+        }
+        match &self.filter {
+            Some(query) => query.matches(project, task),
+            None => true,
         }
     }
 
-    pub fn nav_tasks(&mut self, dir: TaskNavDirection) {
-        let ids: Vec<u32> = self
-            .projects
+    pub fn get_hide_mode(&self, project: &Project) -> HideMode {
+        project.view.hide.unwrap_or(self.hide_default)
+    }
+
+    // Selects a neighbour of the current task when an action hid it, like
+    // completing it while completed tasks are hidden. `previous` is the
+    // visible list before the action.
+    fn keep_selection_visible(&mut self, previous: &[u32]) {
+        let visible = self.get_visible_task_ids();
+        if visible.contains(&self.current_task_id) {
+            return;
+        }
+        let neighbor = previous
             .iter()
-            .find(|proj| proj.is_current)
-            .unwrap()
+            .position(|id| *id == self.current_task_id)
+            .and_then(|i| {
+                previous[i + 1..]
+                    .iter()
+                    .chain(previous[..i].iter().rev())
+                    .find(|id| visible.contains(id))
+            });
+        self.current_task_id = neighbor.or(visible.first()).copied().unwrap_or(0);
+    }
+
+    pub fn init_current_task_id(&mut self) {
+        let ids = self.get_visible_task_ids();
+        self.current_task_id = ids.first().copied().unwrap_or(0);
+    }
+
+    // The ids `nav_tasks` moves through: the whole list, or the current
+    // column on the board.
+    pub fn get_nav_task_ids(&self) -> Vec<u32> {
+        let ids = self.get_visible_task_ids();
+        let Some(state) = self.get_current_task_state().filter(|_| self.board) else {
+            return ids;
+        };
+        let Some(project) = self.get_current_project() else {
+            return ids;
+        };
+        ids.into_iter()
+            .filter(|id| {
+                project
+                    .tasks
+                    .iter()
+                    .any(|t| t.id == *id && t.state == state)
+            })
+            .collect()
+    }
+
+    pub fn get_current_task_state(&self) -> Option<TaskState> {
+        self.get_current_task().map(|t| t.state)
+    }
+
+    pub fn get_current_task(&self) -> Option<&Task> {
+        self.projects
+            .iter()
+            .find(|p| p.id == self.current_project_id)?
             .tasks
             .iter()
+            .find(|t| t.id == self.current_task_id)
+    }
+
+    fn get_current_task_mut(&mut self) -> Option<&mut Task> {
+        self.projects
+            .iter_mut()
+            .find(|p| p.id == self.current_project_id)?
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == self.current_task_id)
+    }
+
+    pub fn nav_tasks(&mut self, dir: TaskNavDirection) {
+        let ids = self.get_nav_task_ids();
+        let Some(last) = ids.len().checked_sub(1) else {
+            return;
+        };
+        let current = ids.iter().position(|&id| id == self.current_task_id);
+        let index = match (dir, current) {
+            (TaskNavDirection::First, _) | (_, None) => 0,
+            (TaskNavDirection::Last, _) => last,
+            (TaskNavDirection::Up, Some(i)) => i.saturating_sub(1),
+            (TaskNavDirection::Down, Some(i)) => (i + 1).min(last),
+            (TaskNavDirection::PageUp, Some(i)) => i.saturating_sub(self.page_size),
+            (TaskNavDirection::PageDown, Some(i)) => (i + self.page_size).min(last),
+        };
+        self.current_task_id = ids[index];
+    }
+
+    // Selects the first task of the next non empty column in that direction.
+    pub fn nav_columns(&mut self, forward: bool) {
+        let columns = TaskState::ALL;
+        let current = self
+            .get_current_task_state()
+            .and_then(|state| columns.iter().position(|s| *s == state));
+        let candidates: Vec<usize> = match (forward, current) {
+            (true, Some(i)) => (i + 1..columns.len()).collect(),
+            (false, Some(i)) => (0..i).rev().collect(),
+            (_, None) => (0..columns.len()).collect(),
+        };
+        let Some(project) = self.get_current_project() else {
+            return;
+        };
+        let visible = self.get_visible_task_ids();
+        for i in candidates {
+            if let Some(id) = visible.iter().find(|id| {
+                project
+                    .tasks
+                    .iter()
+                    .any(|t| t.id == **id && t.state == columns[i])
+            }) {
+                self.current_task_id = *id;
+                return;
+            }
+        }
+    }
+
+    // Moves the current task to the neighbouring column, changing its state.
+    pub fn move_task(&mut self, forward: bool) {
+        let Some(target) = self.get_neighbor_state(forward) else {
+            return;
+        };
+        for project in &mut self.projects {
+            if project.is_current {
+                project.set_task_state(self.current_task_id, target);
+                break;
+            }
+        }
+    }
+
+    // State of the board column next to the current task's one.
+    pub fn get_neighbor_state(&self, forward: bool) -> Option<TaskState> {
+        let columns = TaskState::ALL;
+        let state = self.get_current_task_state()?;
+        let index = columns.iter().position(|s| *s == state).unwrap();
+        match forward {
+            true if index + 1 < columns.len() => Some(columns[index + 1]),
+            false if index > 0 => Some(columns[index - 1]),
+            _ => None,
+        }
+    }
+
+    // Marked tasks of the current project.
+    pub fn get_marked_ids(&self) -> Vec<u32> {
+        self.get_current_project_tasks()
+            .into_iter()
+            .flatten()
+            .filter(|t| self.marked.contains(&(self.current_project_id, t.id)))
             .map(|t| t.id)
-            .collect();
-        match dir {
-            TaskNavDirection::Up => match ids.iter().min() {
-                Some(min_id) => {
-                    if *min_id >= self.current_task_id {
-                        self.current_task_id = ids.iter().min().unwrap().clone();
-                    }
-                    if *min_id != self.current_task_id {
-                        self.current_task_id = ids
-                            .iter()
-                            .filter(|&&id| id < self.current_task_id)
-                            .max()
-                            .unwrap()
-                            .clone();
-                    }
-                }
-                None => {}
-            },
-            TaskNavDirection::Down => match ids.iter().max() {
-                Some(max_id) => {
-                    if *max_id != self.current_task_id {
-                        self.current_task_id = ids
-                            .iter()
-                            .filter(|&&id| id > self.current_task_id)
-                            .min()
-                            .unwrap()
-                            .clone();
-                    }
-                }
-                None => {}
-            },
+            .collect()
+    }
+
+    pub fn is_marked(&self, id: u32) -> bool {
+        self.marked.contains(&(self.current_project_id, id))
+    }
+
+    pub fn mark_tasks(&mut self, ids: &[u32], marked: bool) {
+        for id in ids {
+            let key = (self.current_project_id, *id);
+            if marked {
+                self.marked.insert(key);
+            } else {
+                self.marked.remove(&key);
+            }
+        }
+    }
+
+    pub fn checkpoint(&mut self) {
+        self.undo_stack
+            .push((self.projects.clone(), self.archive.clone()));
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some((projects, archive)) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.projects = projects;
+        self.archive = archive;
+        let task_id = self.current_task_id;
+        if !self.select_project(self.current_project_id) {
+            self.select_project(self.projects[0].id);
+        }
+        // The selection stays on the task when it is still there.
+        if self.get_visible_task_ids().contains(&task_id) {
+            self.current_task_id = task_id;
+        }
+        true
+    }
+
+    // Applies `operation` to the marked tasks after a confirmation, or right
+    // away to the selected task when none is marked.
+    fn start_batch(&mut self, operation: BatchOperation) -> Result<(), Box<dyn Error>> {
+        let marked = self.get_marked_ids();
+        if marked.is_empty() {
+            if self.get_current_task().is_none() {
+                return Ok(());
+            }
+            let batch = Batch {
+                project_id: self.current_project_id,
+                task_ids: vec![self.current_task_id],
+                operation,
+            };
+            self.checkpoint();
+            batch.apply(&mut self.projects);
+            self.ensure_current_task();
+            return self.save_file();
+        }
+        self.batch = Some(Batch {
+            project_id: self.current_project_id,
+            task_ids: marked,
+            operation,
+        });
+        self.screen_mode = ScreenMode::ConfirmingBatch;
+        Ok(())
+    }
+
+    // Archives the finished tasks older than `archive_after_days`.
+    pub fn apply_archive_policy(&mut self, now: u64) -> Result<(), Box<dyn Error>> {
+        let Some(days) = self.archive_after_days else {
+            return Ok(());
+        };
+        let mut count = 0;
+        for project in &mut self.projects {
+            let ids = archive::get_expired_ids(project, days, now);
+            count += ids.len();
+            self.archive.add(project.take_tasks(&ids), now);
+        }
+        if count > 0 {
+            self.ensure_current_task();
+            self.save_file()?;
+            self.save_archive()?;
+            self.message = Some(format!("Archived {count} finished tasks"));
+        }
+        Ok(())
+    }
+
+    pub fn get_archive_entries(&self) -> Vec<&archive::ArchivedTask> {
+        self.archive.get_project_entries(self.current_project_id)
+    }
+
+    // Selects another task when the current one left the project.
+    fn ensure_current_task(&mut self) {
+        if self.get_current_task().is_none() {
+            self.init_current_task_id();
+        }
+    }
+
+    pub fn get_base_mode(&self) -> ScreenMode {
+        if self.board {
+            ScreenMode::Board
+        } else {
+            ScreenMode::Main
         }
     }
 
@@ -163,36 +581,28 @@ impl App {
         }
         ids.sort();
 
-        let next_id;
-
-        match dir {
+        let next_id = match dir {
             SwitchProjectsDirection::Right => {
-                if self.current_project_id == ids.iter().max().unwrap().clone() {
-                    next_id = ids.iter().min().unwrap().clone();
-                    //eprintln!("Next id (reset Right) :  {next_id}");
+                if self.current_project_id == *ids.iter().max().unwrap() {
+                    *ids.iter().min().unwrap()
                 } else {
-                    next_id = ids
-                        .iter()
+                    *ids.iter()
                         .filter(|&&id| id > self.current_project_id)
                         .min()
                         .unwrap()
-                        .clone();
-                    //eprintln!("Next id (normal Right) : {next_id}");
                 }
             }
             SwitchProjectsDirection::Left => {
-                if self.current_project_id == ids.iter().min().unwrap().clone() {
-                    next_id = ids.iter().max().unwrap().clone();
+                if self.current_project_id == *ids.iter().min().unwrap() {
+                    *ids.iter().max().unwrap()
                 } else {
-                    next_id = ids
-                        .iter()
+                    *ids.iter()
                         .filter(|&&id| id < self.current_project_id)
                         .max()
                         .unwrap()
-                        .clone()
                 }
             }
-        }
+        };
 
         for project in &mut self.projects {
             if project.id == next_id {
@@ -203,13 +613,52 @@ impl App {
         self.init_current_task_id();
     }
 
-    pub fn get_current_project_name(&self) -> String {
+    pub fn select_project(&mut self, id: u32) -> bool {
+        if !self.projects.iter().any(|p| p.id == id) {
+            return false;
+        }
+        for project in &mut self.projects {
+            if project.is_current != (project.id == id) {
+                project.toggle_current();
+            }
+        }
+        self.current_project_id = id;
+        self.init_current_task_id();
+        true
+    }
+
+    // Looks in the current project first, then in the others.
+    pub fn select_task(&mut self, id: u32) -> bool {
+        let Some(project_id) = self
+            .projects
+            .iter()
+            .filter(|p| p.tasks.iter().any(|t| t.id == id))
+            .min_by_key(|p| p.id != self.current_project_id)
+            .map(|p| p.id)
+        else {
+            return false;
+        };
+        self.select_project(project_id);
+        self.current_task_id = id;
+        true
+    }
+
+    pub fn find_project(&self, name: &str) -> Option<u32> {
         self.projects
             .iter()
-            .find(|prj| prj.is_current)
-            .unwrap()
-            .name
-            .clone()
+            .find(|p| p.name == name)
+            .or_else(|| {
+                self.projects
+                    .iter()
+                    .find(|p| p.name.to_lowercase() == name.to_lowercase())
+            })
+            .map(|p| p.id)
+    }
+
+    pub fn get_current_project_name(&self) -> String {
+        self.get_current_project()
+            .map(|prj| prj.name.clone())
+            .unwrap_or_default()
     }
 
     pub fn get_current_project_tasks(&self) -> Option<&Vec<Task>> {
@@ -230,15 +679,21 @@ impl App {
         }
     }
 
+    // The selection moves to the previous task in the list, or the next one
+    // when the first task is deleted.
     pub fn delete_task(&mut self, id: u32) {
+        let ids = self.get_nav_task_ids();
+        let neighbor = ids.iter().position(|&i| i == id).and_then(|i| match i {
+            0 => ids.get(1),
+            _ => ids.get(i - 1),
+        });
+        self.current_task_id = neighbor.copied().unwrap_or(0);
         for project in &mut self.projects {
             if project.is_current {
                 project.delete_task(id);
                 break;
             }
         }
-
-        self.nav_tasks(TaskNavDirection::Up);
     }
 
     pub fn toggle_task_state(&mut self) {
@@ -250,6 +705,15 @@ impl App {
         }
     }
 
+    pub fn complete_task(&mut self) {
+        for project in &mut self.projects {
+            if project.is_current {
+                project.complete_task(self.current_task_id);
+                break;
+            }
+        }
+    }
+
     pub fn rename_task(&mut self, new_title: String) {
         for project in &mut self.projects {
             if project.is_current {
@@ -278,16 +742,8 @@ impl App {
     }
 
     pub fn task_to_cursor_manager(&mut self) {
-        for project in &mut self.projects {
-            if project.is_current {
-                let current_task = project
-                    .tasks
-                    .iter()
-                    .find(|task| task.id == self.current_task_id)
-                    .unwrap();
-                self.cursor_manager.set_string(current_task.title.clone());
-                break;
-            }
+        if let Some(title) = self.get_current_task().map(|task| task.title.clone()) {
+            self.cursor_manager.set_string(title);
         }
     }
 
@@ -312,4 +768,1348 @@ impl App {
             }
         }
     }
+
+    pub fn import_org(&mut self, content: &str) -> usize {
+        let next_project_id = self.projects.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+        let projects = org::from_org(content, next_project_id, self.next_task_id);
+        self.next_task_id += projects.iter().map(|p| p.tasks.len() as u32).sum::<u32>();
+        let count = projects.len();
+        let had_current = self.projects.iter().any(|p| p.is_current);
+        self.projects.extend(projects);
+        if !had_current {
+            self.get_current_project_id();
+            self.init_current_task_id();
+        }
+        count
+    }
+
+    pub fn export_org(&self) -> String {
+        org::to_org(&self.projects)
+    }
+
+    pub fn filter_to_cursor_manager(&mut self) {
+        self.filter_error = None;
+        self.cursor_manager.set_string(self.filter_string.clone());
+    }
+
+    pub fn apply_filter(&mut self, filter_string: String) -> Result<(), QueryError> {
+        let query = Query::parse(&filter_string)?;
+        self.filter = if filter_string.trim().is_empty() {
+            None
+        } else {
+            Some(query)
+        };
+        self.filter_string = filter_string;
+        self.filter_error = None;
+        if !self.get_visible_task_ids().contains(&self.current_task_id) {
+            self.init_current_task_id();
+        }
+        Ok(())
+    }
+
+    // Every visible task of every project, in display order.
+    pub fn get_task_positions(&self) -> Vec<(u32, u32)> {
+        self.get_visible_tasks()
+            .map(|(project, task)| (project.id, task.id))
+            .collect()
+    }
+
+    pub fn get_search_matches(&self) -> Vec<(u32, u32)> {
+        self.get_visible_tasks()
+            .filter(|(_, task)| search::is_match(&task.title, &self.search))
+            .map(|(project, task)| (project.id, task.id))
+            .collect()
+    }
+
+    fn get_visible_tasks(&self) -> impl Iterator<Item = (&Project, &Task)> {
+        let mut projects: Vec<&Project> = self.projects.iter().collect();
+        projects.sort_by_key(|p| p.id);
+        projects.into_iter().flat_map(move |project| {
+            project
+                .get_sorted_tasks()
+                .into_iter()
+                .filter(move |task| self.is_task_visible(project, task))
+                .map(move |task| (project, task))
+        })
+    }
+
+    // Moves to the next (or previous) match, wrapping around the projects.
+    // With `include_current`, the current task counts as the next match.
+    pub fn jump_to_match(&mut self, forward: bool, include_current: bool) -> bool {
+        let positions = self.get_task_positions();
+        let matches = self.get_search_matches();
+        let current = (self.current_project_id, self.current_task_id);
+        let current_index = positions.iter().position(|p| *p == current);
+        let match_indexes: Vec<usize> = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, position)| matches.contains(position))
+            .map(|(i, _)| i)
+            .collect();
+
+        let target = match (forward, current_index) {
+            (_, None) => match_indexes.first(),
+            (true, Some(current)) => match_indexes
+                .iter()
+                .find(|&&i| i > current || (include_current && i == current))
+                .or(match_indexes.first()),
+            (false, Some(current)) => match_indexes
+                .iter()
+                .rev()
+                .find(|&&i| i < current)
+                .or(match_indexes.last()),
+        };
+        let Some(&(project_id, task_id)) = target.map(|&i| &positions[i]) else {
+            return false;
+        };
+        self.select_project(project_id);
+        self.current_task_id = task_id;
+        true
+    }
+
+    pub fn update_search(&mut self) {
+        self.search = self.cursor_manager.string.clone();
+        let (project_id, task_id) = self.search_origin;
+        self.select_project(project_id);
+        self.current_task_id = task_id;
+        if !self.search.is_empty() {
+            self.jump_to_match(true, true);
+        }
+    }
+
+    pub fn dispatch(&mut self, action: Action) -> Result<(), Box<dyn Error>> {
+        let visible = self.get_visible_task_ids();
+        let result = self.apply_action(action);
+        self.keep_selection_visible(&visible);
+        result
+    }
+
+    fn apply_action(&mut self, action: Action) -> Result<(), Box<dyn Error>> {
+        match action {
+            Action::Quit => {
+                self.save_file()?;
+                self.should_quit = true;
+            }
+            Action::Help => {
+                self.show_help = true;
+                self.help_offset = 0;
+            }
+            Action::PreviousProject => self.switch_project(SwitchProjectsDirection::Left),
+            Action::NextProject => self.switch_project(SwitchProjectsDirection::Right),
+            Action::SelectProject(id) => {
+                if !self.select_project(id) {
+                    return Err(format!("no project with id {id}").into());
+                }
+            }
+            Action::PreviousTask => self.nav_tasks(TaskNavDirection::Up),
+            Action::NextTask => self.nav_tasks(TaskNavDirection::Down),
+            Action::FirstTask => self.nav_tasks(TaskNavDirection::First),
+            Action::LastTask => self.nav_tasks(TaskNavDirection::Last),
+            Action::PageUp => self.nav_tasks(TaskNavDirection::PageUp),
+            Action::PageDown => self.nav_tasks(TaskNavDirection::PageDown),
+            Action::SelectTask(id) => {
+                if !self.select_task(id) {
+                    return Err(format!("no task with id {id}").into());
+                }
+            }
+            Action::ToggleTask | Action::CompleteTask | Action::CancelTask
+                if !self.get_marked_ids().is_empty() =>
+            {
+                let all_completed = self.get_current_project_tasks().is_some_and(|tasks| {
+                    tasks
+                        .iter()
+                        .filter(|t| self.is_marked(t.id))
+                        .all(|t| t.state == TaskState::Completed)
+                });
+                let state = match action {
+                    Action::ToggleTask if all_completed => TaskState::Todo,
+                    Action::CancelTask => TaskState::Canceled,
+                    _ => TaskState::Completed,
+                };
+                self.start_batch(BatchOperation::SetState(state))?;
+            }
+            Action::MoveTaskLeft | Action::MoveTaskRight if !self.get_marked_ids().is_empty() => {
+                if let Some(state) = self.get_neighbor_state(action == Action::MoveTaskRight) {
+                    self.start_batch(BatchOperation::SetState(state))?;
+                }
+            }
+            // No undo step for a change with no task to apply to.
+            Action::ToggleTask
+            | Action::CompleteTask
+            | Action::CancelTask
+            | Action::MoveTaskLeft
+            | Action::MoveTaskRight
+                if self.get_current_task().is_none() => {}
+            Action::ToggleTask => {
+                self.checkpoint();
+                self.toggle_task_state();
+            }
+            Action::CompleteTask => {
+                self.checkpoint();
+                self.complete_task();
+            }
+            Action::CancelTask => {
+                self.checkpoint();
+                self.cancel_task();
+            }
+            Action::MoveTaskLeft => {
+                self.checkpoint();
+                self.move_task(false);
+            }
+            Action::MoveTaskRight => {
+                self.checkpoint();
+                self.move_task(true);
+            }
+            Action::ToggleMark => {
+                let id = self.current_task_id;
+                if self.get_current_task().is_some() {
+                    self.mark_tasks(&[id], !self.is_marked(id));
+                    self.mark_anchor = Some(id);
+                    self.nav_tasks(TaskNavDirection::Down);
+                }
+            }
+            Action::MarkRange => {
+                let ids = self.get_nav_task_ids();
+                let current = ids.iter().position(|id| *id == self.current_task_id);
+                let anchor = ids
+                    .iter()
+                    .position(|id| Some(*id) == self.mark_anchor)
+                    .or(current);
+                if let (Some(a), Some(b)) = (anchor, current) {
+                    self.mark_tasks(&ids[a.min(b)..=a.max(b)], true);
+                }
+            }
+            Action::MarkAll => {
+                let ids = self.get_nav_task_ids();
+                let all_marked = ids.iter().all(|id| self.is_marked(*id));
+                self.mark_tasks(&ids, !all_marked);
+            }
+            Action::MarkSameState => {
+                if let (Some(state), Some(project)) =
+                    (self.get_current_task_state(), self.get_current_project())
+                {
+                    let ids: Vec<u32> = self
+                        .get_nav_task_ids()
+                        .into_iter()
+                        .filter(|id| {
+                            project
+                                .tasks
+                                .iter()
+                                .any(|t| t.id == *id && t.state == state)
+                        })
+                        .collect();
+                    self.mark_tasks(&ids, true);
+                }
+            }
+            Action::ClearMarks => {
+                let ids = self.get_marked_ids();
+                self.mark_tasks(&ids, false);
+            }
+            Action::MoveTasks => {
+                self.screen_mode = ScreenMode::MovingTasks;
+                self.cursor_manager.clear();
+            }
+            Action::TagTasks => {
+                self.screen_mode = ScreenMode::TaggingTasks;
+                self.cursor_manager.clear();
+            }
+            Action::Undo => {
+                if self.undo() {
+                    self.save_file()?;
+                    self.save_archive()?;
+                    self.message = Some(String::from("Undone"));
+                } else {
+                    self.message = Some(String::from("Nothing to undo"));
+                }
+            }
+            Action::AddTask => self.screen_mode = ScreenMode::AddingTask,
+            Action::CreateTask(title) => {
+                self.checkpoint();
+                self.add_task(title);
+                self.save_file()?;
+            }
+            Action::RenameTask => {
+                if self.get_current_task().is_none() {
+                    self.message = Some(String::from("No task to rename"));
+                    return Ok(());
+                }
+                self.screen_mode = ScreenMode::RenamingTask;
+                self.task_to_cursor_manager();
+            }
+            Action::EditTask => {
+                let Some(task) = self.get_current_task() else {
+                    self.message = Some(String::from("No task to edit"));
+                    return Ok(());
+                };
+                // A document that failed to parse is reopened as it was left.
+                self.editor_document = match &self.editor_draft {
+                    Some((id, draft)) if *id == task.id => Some(draft.clone()),
+                    _ => Some(editor::to_document(task)),
+                };
+            }
+            Action::ApplyEdit(document) => match editor::from_document(&document) {
+                Ok(edit) => {
+                    self.editor_draft = None;
+                    self.checkpoint();
+                    if let Some(task) = self.get_current_task_mut() {
+                        task.rename(edit.title);
+                        task.set_state(edit.state);
+                        if task.notes != edit.notes {
+                            task.notes = edit.notes;
+                            task.touch();
+                        }
+                    }
+                    self.save_file()?;
+                }
+                Err(e) => {
+                    let draft = editor::add_error(&document, &e.to_string());
+                    self.editor_draft = Some((self.current_task_id, draft));
+                    self.message = Some(format!("Task not changed, {e}. Edit it again to fix it"));
+                }
+            },
+            Action::DeleteTask if !self.get_marked_ids().is_empty() => {
+                self.start_batch(BatchOperation::Delete)?;
+            }
+            Action::DeleteTask => self.screen_mode = ScreenMode::DeletingTask,
+            Action::NewProject => {
+                self.add_project();
+                self.screen_mode = ScreenMode::RenamingProject;
+            }
+            Action::RenameProject => {
+                self.screen_mode = ScreenMode::RenamingProject;
+                self.project_to_cursor_manager();
+            }
+            Action::Filter => {
+                self.screen_mode = ScreenMode::Filtering;
+                self.filter_to_cursor_manager();
+            }
+            Action::ClearFilter => {
+                self.apply_filter(String::new())?;
+            }
+            Action::Search => {
+                self.screen_mode = ScreenMode::Searching;
+                self.search_origin = (self.current_project_id, self.current_task_id);
+                self.cursor_manager.clear();
+                self.search.clear();
+            }
+            Action::NextMatch | Action::PreviousMatch => {
+                if self.search.is_empty() {
+                    self.message = Some(String::from("No active search, press / to search"));
+                } else if !self.jump_to_match(action == Action::NextMatch, false) {
+                    self.message = Some(format!("No match for '{}'", self.search));
+                }
+            }
+            Action::ToggleSidebar => {
+                self.show_sidebar = !self.show_sidebar;
+                if !self.show_sidebar && self.screen_mode == ScreenMode::Sidebar {
+                    self.screen_mode = self.get_base_mode();
+                }
+            }
+            Action::ToggleBoard => {
+                self.board = !self.board;
+                if matches!(self.screen_mode, ScreenMode::Main | ScreenMode::Board) {
+                    self.screen_mode = self.get_base_mode();
+                }
+            }
+            Action::CycleSort | Action::ReverseSort | Action::ToggleGrouping => {
+                if let Some(project) = self
+                    .projects
+                    .iter_mut()
+                    .find(|p| p.id == self.current_project_id)
+                {
+                    let view = &mut project.view;
+                    match action {
+                        Action::CycleSort => view.sort = view.sort.next(),
+                        Action::ReverseSort => view.descending = !view.descending,
+                        _ => view.grouped = !view.grouped,
+                    }
+                    self.message = Some(match view.get_description().as_str() {
+                        "" => String::from("Manual order"),
+                        description => format!("Tasks {description}"),
+                    });
+                    self.save_file()?;
+                }
+            }
+            Action::ToggleDates => self.show_dates = !self.show_dates,
+            Action::CycleHidden => {
+                let default = self.hide_default;
+                if let Some(project) = self
+                    .projects
+                    .iter_mut()
+                    .find(|p| p.id == self.current_project_id)
+                {
+                    let hide = project.view.hide.unwrap_or(default).next();
+                    project.view.hide = (hide != default).then_some(hide);
+                    self.message = Some(match hide {
+                        HideMode::None => String::from("Showing all tasks"),
+                        _ => format!("Hiding {} tasks", hide.get_name()),
+                    });
+                    self.save_file()?;
+                }
+            }
+            Action::PreviousColumn => self.nav_columns(false),
+            Action::NextColumn => self.nav_columns(true),
+            Action::SwitchFocus => {
+                self.screen_mode = match self.screen_mode {
+                    ScreenMode::Sidebar => self.get_base_mode(),
+                    _ => ScreenMode::Sidebar,
+                };
+            }
+            Action::Palette => {
+                self.screen_mode = ScreenMode::Palette;
+                self.palette_index = 0;
+                self.cursor_manager.clear();
+            }
+            Action::ExportOrg => {
+                let path = self.file_manager.get_export_path(EXPORT_ORG_EXTENSION);
+                fs::write(&path, self.export_org())?;
+                self.message = Some(format!("Exported to {}", path.display()));
+            }
+            Action::ExportReport => {
+                let path = self.file_manager.get_export_path(EXPORT_REPORT_EXTENSION);
+                fs::write(&path, report::to_html(&self.projects))?;
+                self.message = Some(format!("Exported to {}", path.display()));
+            }
+            Action::InsertChar(c) => {
+                match (self.is_prompt(), &mut self.vi) {
+                    (true, Some(vi)) => vi.handle_char(c, &mut self.cursor_manager),
+                    _ => self.cursor_manager.insert(c),
+                }
+                self.input_changed();
+            }
+            Action::Paste(text) => {
+                if KeyContext::from_mode(&self.screen_mode).is_input() {
+                    self.cursor_manager.insert_str(&text);
+                    self.input_changed();
+                }
+            }
+            Action::Yank => {
+                self.cursor_manager.yank();
+                self.input_changed();
+            }
+            Action::DeleteChar => {
+                match (self.is_prompt(), &mut self.vi) {
+                    (true, Some(vi)) => vi.handle_backspace(&mut self.cursor_manager),
+                    _ => self.cursor_manager.delete(),
+                }
+                self.input_changed();
+            }
+            Action::DeleteNextChar => {
+                self.cursor_manager.delete_next();
+                self.input_changed();
+            }
+            Action::DeleteWordLeft => {
+                self.cursor_manager.kill(CursorDirection::WordLeft);
+                self.input_changed();
+            }
+            Action::DeleteWordRight => {
+                self.cursor_manager.kill(CursorDirection::WordRight);
+                self.input_changed();
+            }
+            Action::KillToStart => {
+                self.cursor_manager.kill(CursorDirection::Start);
+                self.input_changed();
+            }
+            Action::KillToEnd => {
+                self.cursor_manager.kill(CursorDirection::End);
+                self.input_changed();
+            }
+            Action::ArchiveDone => {
+                let finished: Vec<u32> = self
+                    .get_current_project_tasks()
+                    .into_iter()
+                    .flatten()
+                    .filter(|t| t.state != TaskState::Todo)
+                    .map(|t| t.id)
+                    .collect();
+                if finished.is_empty() {
+                    self.message = Some(String::from("No finished task to archive"));
+                } else {
+                    self.batch = Some(Batch {
+                        project_id: self.current_project_id,
+                        task_ids: finished,
+                        operation: BatchOperation::Archive,
+                    });
+                    self.screen_mode = ScreenMode::ConfirmingBatch;
+                }
+            }
+            Action::ShowArchive => {
+                self.screen_mode = match self.screen_mode {
+                    ScreenMode::Archive => self.get_base_mode(),
+                    _ => ScreenMode::Archive,
+                };
+                self.archive_index = 0;
+            }
+            Action::RestoreTask => {
+                let entries = self.get_archive_entries();
+                let index = self.archive_index.min(entries.len().saturating_sub(1));
+                let Some(id) = entries.get(index).map(|e| e.task.id) else {
+                    return Ok(());
+                };
+                self.checkpoint();
+                if let Some(task) = self.archive.take(self.current_project_id, id) {
+                    self.message = Some(format!("Restored '{}'", task.title));
+                    if let Some(project) = self
+                        .projects
+                        .iter_mut()
+                        .find(|p| p.id == self.current_project_id)
+                    {
+                        project.insert_task(task);
+                    }
+                }
+                self.save_file()?;
+                self.save_archive()?;
+            }
+            Action::PreviousItem if self.show_help => {
+                self.help_offset = self.help_offset.saturating_sub(1);
+            }
+            Action::NextItem if self.show_help => self.help_offset += 1,
+            Action::PreviousItem if self.screen_mode == ScreenMode::Archive => {
+                self.archive_index = self.archive_index.saturating_sub(1);
+            }
+            Action::NextItem if self.screen_mode == ScreenMode::Archive => {
+                let count = self.get_archive_entries().len();
+                self.archive_index = (self.archive_index + 1).min(count.saturating_sub(1));
+            }
+            Action::PreviousItem => self.palette_index = self.palette_index.saturating_sub(1),
+            Action::NextItem => {
+                let count = palette::get_matches(self, &self.cursor_manager.string).len();
+                if self.palette_index + 1 < count {
+                    self.palette_index += 1;
+                }
+            }
+            Action::CursorLeft => self.cursor_manager.move_cursor(CursorDirection::Left),
+            Action::CursorRight => self.cursor_manager.move_cursor(CursorDirection::Right),
+            Action::WordLeft => self.cursor_manager.move_cursor(CursorDirection::WordLeft),
+            Action::WordRight => self.cursor_manager.move_cursor(CursorDirection::WordRight),
+            Action::CursorStart => self.cursor_manager.move_cursor(CursorDirection::Start),
+            Action::CursorEnd => self.cursor_manager.move_cursor(CursorDirection::End),
+            Action::PreviousHistory | Action::NextHistory => {
+                if self.screen_mode == ScreenMode::AddingTask {
+                    let entry = match action {
+                        Action::PreviousHistory => {
+                            self.history.previous(&self.cursor_manager.string)
+                        }
+                        _ => self.history.next(),
+                    };
+                    if let Some(entry) = entry {
+                        self.cursor_manager.set_string(entry.to_string());
+                    }
+                }
+            }
+            Action::Complete => {
+                let candidates = self.get_completions();
+                if self.cursor_manager.complete(&candidates) {
+                    self.input_changed();
+                }
+            }
+            Action::Abort => {
+                let prompt = self.is_prompt();
+                if let Some(vi) = &mut self.vi {
+                    if prompt && vi.handle_escape(&mut self.cursor_manager) {
+                        return Ok(());
+                    }
+                    vi.reset();
+                }
+                self.history.reset();
+                if self.screen_mode == ScreenMode::Searching {
+                    self.cursor_manager.clear();
+                    self.update_search();
+                }
+                self.screen_mode = self.get_base_mode();
+                self.filter_error = None;
+                self.batch = None;
+                self.cursor_manager.clear();
+            }
+            Action::Confirm => {
+                if self.screen_mode == ScreenMode::DeletingTask {
+                    self.checkpoint();
+                    self.delete_task(self.current_task_id);
+                    self.save_file()?;
+                    self.screen_mode = self.get_base_mode();
+                }
+                if let Some(batch) = self.batch.take() {
+                    self.checkpoint();
+                    let removed = batch.apply(&mut self.projects);
+                    if batch.operation == BatchOperation::Archive {
+                        self.archive.add(removed, date::now());
+                        self.save_archive()?;
+                    }
+                    self.mark_tasks(&batch.task_ids, false);
+                    self.ensure_current_task();
+                    self.save_file()?;
+                    self.screen_mode = self.get_base_mode();
+                }
+            }
+            Action::Validate => {
+                if let Some(vi) = &mut self.vi {
+                    vi.reset();
+                }
+                self.validate_input()?
+            }
+        }
+        Ok(())
+    }
+
+    // Tab completion candidates: project names, then the words of past
+    // entries (most recent first) and of every task title, tags included.
+    pub fn get_completions(&self) -> Vec<String> {
+        let mut candidates: Vec<String> = self.projects.iter().map(|p| p.name.clone()).collect();
+        let titles = self.history.entries.iter().rev().chain(
+            self.projects
+                .iter()
+                .flat_map(|p| p.tasks.iter())
+                .map(|t| &t.title),
+        );
+        for title in titles {
+            for word in title.split_whitespace() {
+                let word = word.trim_matches(|c: char| c.is_ascii_punctuation() && c != '#');
+                if !word.is_empty() && !candidates.iter().any(|c| c == word) {
+                    candidates.push(word.to_string());
+                }
+            }
+        }
+        candidates
+    }
+
+    // Text prompts, where vi mode applies. The palette keeps plain editing.
+    fn is_prompt(&self) -> bool {
+        KeyContext::from_mode(&self.screen_mode) == KeyContext::Prompt
+    }
+
+    pub fn is_vi_normal(&self) -> bool {
+        self.is_prompt()
+            && self
+                .vi
+                .as_ref()
+                .is_some_and(|vi| vi.state == ViState::Normal)
+    }
+
+    // The palette ranking and the live search follow what is typed.
+    fn input_changed(&mut self) {
+        self.palette_index = 0;
+        if self.screen_mode == ScreenMode::Searching {
+            self.update_search();
+        }
+    }
+
+    fn validate_input(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.screen_mode {
+            ScreenMode::AddingTask => {
+                let new_task = self.cursor_manager.validate();
+                self.history.push(&new_task);
+                self.file_manager.save_history(&self.history.entries)?;
+                self.checkpoint();
+                self.add_task(new_task);
+            }
+            ScreenMode::RenamingTask => {
+                let new_task_name = self.cursor_manager.validate();
+                self.checkpoint();
+                self.rename_task(new_task_name);
+            }
+            ScreenMode::RenamingProject => {
+                let new_project_name = self.cursor_manager.validate();
+                self.checkpoint();
+                self.rename_project(new_project_name);
+            }
+            ScreenMode::MovingTasks => {
+                let name = self.cursor_manager.string.trim();
+                let Some(project) = self
+                    .projects
+                    .iter()
+                    .find(|p| p.name.to_lowercase() == name.to_lowercase())
+                else {
+                    self.message = Some(format!("No project named '{name}'"));
+                    return Ok(());
+                };
+                let id = project.id;
+                self.cursor_manager.clear();
+                self.screen_mode = self.get_base_mode();
+                if id != self.current_project_id {
+                    self.start_batch(BatchOperation::Move(id))?;
+                }
+                return Ok(());
+            }
+            ScreenMode::TaggingTasks => {
+                let tags = batch::parse_tags(&self.cursor_manager.validate());
+                self.screen_mode = self.get_base_mode();
+                if !tags.is_empty() {
+                    self.start_batch(BatchOperation::Tag(tags))?;
+                }
+                return Ok(());
+            }
+            ScreenMode::Filtering => {
+                if let Err(e) = self.apply_filter(self.cursor_manager.string.clone()) {
+                    self.filter_error = Some(e.to_string());
+                    return Ok(());
+                }
+                self.cursor_manager.clear();
+                self.screen_mode = self.get_base_mode();
+                return Ok(());
+            }
+            ScreenMode::Searching => {
+                if !self.search.is_empty() && self.get_search_matches().is_empty() {
+                    self.message = Some(format!("No match for '{}'", self.search));
+                    self.search.clear();
+                }
+                self.cursor_manager.clear();
+                self.screen_mode = self.get_base_mode();
+                return Ok(());
+            }
+            ScreenMode::Palette => {
+                let mut matches = palette::get_matches(self, &self.cursor_manager.string);
+                self.cursor_manager.clear();
+                self.screen_mode = self.get_base_mode();
+                if self.palette_index < matches.len() {
+                    let entry = matches.swap_remove(self.palette_index);
+                    self.dispatch(entry.action)?;
+                }
+                return Ok(());
+            }
+            ScreenMode::Main
+            | ScreenMode::Board
+            | ScreenMode::Sidebar
+            | ScreenMode::DeletingTask
+            | ScreenMode::ConfirmingBatch
+            | ScreenMode::Archive => return Ok(()),
+        }
+        self.screen_mode = self.get_base_mode();
+        self.save_file()
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod app_test {
+    use super::*;
+    use crate::{
+        comps::TaskState,
+        testing::{build_app as build, TempFiles},
+    };
+    use std::fs;
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.dispatch(Action::InsertChar(c)).unwrap();
+        }
+    }
+
+    fn current_titles(app: &App) -> Vec<String> {
+        app.get_current_project_tasks()
+            .unwrap()
+            .iter()
+            .map(|t| t.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn add_task() {
+        let (mut app, _files) = build("add_task");
+
+        app.dispatch(Action::AddTask).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::AddingTask);
+        type_text(&mut app, "Salut");
+        app.dispatch(Action::CursorLeft).unwrap();
+        app.dispatch(Action::DeleteChar).unwrap();
+        app.dispatch(Action::Validate).unwrap();
+
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+        assert_eq!(current_titles(&app).last().unwrap(), "Salt");
+        assert_eq!(app.current_task_id, app.next_task_id - 1);
+    }
+
+    #[test]
+    fn abort() {
+        let (mut app, _files) = build("abort");
+
+        app.dispatch(Action::RenameTask).unwrap();
+        type_text(&mut app, " !");
+        app.dispatch(Action::Abort).unwrap();
+
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+        assert!(app.cursor_manager.string.is_empty());
+        assert_eq!(current_titles(&app)[0], "Welcome in tuido");
+    }
+
+    #[test]
+    fn paste() {
+        let (mut app, _files) = build("paste");
+
+        app.dispatch(Action::Paste(String::from("ignored")))
+            .unwrap();
+        app.dispatch(Action::AddTask).unwrap();
+        type_text(&mut app, "Buy bread");
+        app.dispatch(Action::DeleteWordLeft).unwrap();
+        app.dispatch(Action::Paste(String::from("milk\nand eggs")))
+            .unwrap();
+        app.dispatch(Action::CursorStart).unwrap();
+        app.dispatch(Action::Yank).unwrap();
+        app.dispatch(Action::Validate).unwrap();
+
+        assert!(app.cursor_manager.string.is_empty());
+        assert_eq!(
+            current_titles(&app).last().unwrap(),
+            "breadBuy milk and eggs"
+        );
+    }
+
+    #[test]
+    fn vi_mode() {
+        let (mut app, _files) = build("vi_mode");
+        app.vi = Some(Vi::new());
+
+        app.dispatch(Action::AddTask).unwrap();
+        type_text(&mut app, "milk");
+        app.dispatch(Action::Abort).unwrap();
+        assert!(app.is_vi_normal());
+        type_text(&mut app, "0iBuy ");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(current_titles(&app).last().unwrap(), "Buy milk");
+
+        // The next prompt starts in insert state, two `Esc` cancel it.
+        app.dispatch(Action::RenameTask).unwrap();
+        assert!(!app.is_vi_normal());
+        type_text(&mut app, "x");
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::RenamingTask);
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+    }
+
+    #[test]
+    fn edit_task() {
+        let (mut app, _files) = build("edit_task");
+
+        app.dispatch(Action::EditTask).unwrap();
+        let document = app.editor_document.take().unwrap();
+        assert!(document.contains("Title: Welcome in tuido\n"));
+
+        // A broken document is kept and reopened with the error on top.
+        let broken = document.replace("State: Todo", "State: Doing");
+        app.dispatch(Action::ApplyEdit(broken)).unwrap();
+        assert!(app
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("unknown state 'Doing'"));
+        app.dispatch(Action::EditTask).unwrap();
+        let draft = app.editor_document.take().unwrap();
+        assert!(draft.starts_with("# Error: line 5: unknown state 'Doing'"));
+
+        let fixed = draft.replace("Doing", "Completed") + "Some notes\n";
+        app.dispatch(Action::ApplyEdit(fixed)).unwrap();
+        let task = app.get_current_task().unwrap();
+        assert_eq!(task.state, TaskState::Completed);
+        assert_eq!(task.notes, "Some notes");
+        assert!(app.editor_draft.is_none());
+    }
+
+    #[test]
+    fn history_and_completion() {
+        let (mut app, files) = build("history");
+
+        app.dispatch(Action::AddTask).unwrap();
+        type_text(&mut app, "Buy milk #shopping");
+        app.dispatch(Action::Validate).unwrap();
+
+        app.dispatch(Action::AddTask).unwrap();
+        type_text(&mut app, "Call");
+        app.dispatch(Action::PreviousHistory).unwrap();
+        assert_eq!(app.cursor_manager.string, "Buy milk #shopping");
+        app.dispatch(Action::NextHistory).unwrap();
+        assert_eq!(app.cursor_manager.string, "Call");
+        type_text(&mut app, " about #sh");
+        app.dispatch(Action::Complete).unwrap();
+        assert_eq!(app.cursor_manager.string, "Call about #shopping");
+        app.dispatch(Action::Abort).unwrap();
+
+        let app = App::from_file_manager(files.get_file_manager()).unwrap();
+        assert_eq!(app.history.entries, vec!["Buy milk #shopping"]);
+    }
+
+    #[test]
+    fn batch() {
+        let (mut app, _files) = build("batch");
+        for title in ["Three", "Four", "Five"] {
+            app.dispatch(Action::CreateTask(title.to_string())).unwrap();
+        }
+
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::ToggleMark).unwrap();
+        app.dispatch(Action::NextTask).unwrap();
+        app.dispatch(Action::MarkRange).unwrap();
+        assert_eq!(app.get_marked_ids(), vec![1, 2, 3]);
+
+        // One confirmation for the whole batch.
+        app.dispatch(Action::CompleteTask).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::ConfirmingBatch);
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Todo));
+        app.dispatch(Action::ToggleTask).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        let states = |app: &App| -> Vec<TaskState> {
+            app.get_current_project_tasks()
+                .unwrap()
+                .iter()
+                .map(|t| t.state)
+                .collect()
+        };
+        assert_eq!(states(&app)[..3], [TaskState::Completed; 3]);
+        assert_eq!(states(&app)[3], TaskState::Todo);
+        assert!(app.get_marked_ids().is_empty());
+
+        // And a single undo step.
+        app.dispatch(Action::Undo).unwrap();
+        assert_eq!(states(&app), vec![TaskState::Todo; 5]);
+
+        app.dispatch(Action::MarkAll).unwrap();
+        app.dispatch(Action::TagTasks).unwrap();
+        type_text(&mut app, "#home");
+        app.dispatch(Action::Validate).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert!(current_titles(&app).iter().all(|t| t.ends_with(" #home")));
+
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::ToggleMark).unwrap();
+        app.dispatch(Action::ToggleMark).unwrap();
+        app.dispatch(Action::MoveTasks).unwrap();
+        type_text(&mut app, "other project");
+        app.dispatch(Action::Validate).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert_eq!(
+            current_titles(&app),
+            vec!["Three #home", "Four #home", "Five #home"]
+        );
+
+        app.dispatch(Action::MarkSameState).unwrap();
+        app.dispatch(Action::DeleteTask).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert!(current_titles(&app).is_empty());
+        app.dispatch(Action::Undo).unwrap();
+        assert_eq!(current_titles(&app).len(), 3);
+    }
+
+    #[test]
+    fn undo_selection() {
+        let (mut app, _files) = build("undo_selection");
+        for title in ["Three", "Four"] {
+            app.dispatch(Action::CreateTask(title.to_string())).unwrap();
+        }
+        app.dispatch(Action::LastTask).unwrap();
+        let id = app.current_task_id;
+        app.dispatch(Action::ToggleTask).unwrap();
+        app.dispatch(Action::Undo).unwrap();
+        assert_eq!(app.current_task_id, id);
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Todo));
+
+        // Nothing selected, nothing to undo.
+        app.dispatch(Action::NewProject).unwrap();
+        type_text(&mut app, "Empty");
+        app.dispatch(Action::Validate).unwrap();
+        let steps = app.undo_stack.len();
+        for action in [
+            Action::ToggleTask,
+            Action::CancelTask,
+            Action::MoveTaskRight,
+        ] {
+            app.dispatch(action).unwrap();
+        }
+        assert_eq!(app.undo_stack.len(), steps);
+    }
+
+    #[test]
+    fn sort_and_group() {
+        let (mut app, files) = build("sort_and_group");
+        for title in ["zeta", "alpha"] {
+            app.dispatch(Action::CreateTask(title.to_string())).unwrap();
+        }
+        app.dispatch(Action::ToggleTask).unwrap();
+
+        app.dispatch(Action::CycleSort).unwrap();
+        assert_eq!(app.get_visible_task_ids(), vec![4, 1, 2, 3]);
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::NextTask).unwrap();
+        assert_eq!(app.current_task_id, 1);
+
+        app.dispatch(Action::ToggleGrouping).unwrap();
+        app.dispatch(Action::ReverseSort).unwrap();
+        assert_eq!(app.get_visible_task_ids(), vec![3, 2, 1, 4]);
+        app.dispatch(Action::LastTask).unwrap();
+        app.dispatch(Action::PreviousTask).unwrap();
+        assert_eq!(app.current_task_id, 1);
+
+        // The view is saved with the project.
+        let reloaded = App::from_file_manager(files.get_file_manager()).unwrap();
+        assert_eq!(reloaded.get_visible_task_ids(), vec![3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn hide_finished() {
+        let (mut app, _files) = build("hide_finished");
+        app.dispatch(Action::CreateTask(String::from("Three")))
+            .unwrap();
+        app.dispatch(Action::CycleHidden).unwrap();
+        assert_eq!(app.message.as_deref(), Some("Hiding completed tasks"));
+
+        // Completing the selected task hides it and selects the next one.
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::NextTask).unwrap();
+        app.dispatch(Action::ToggleTask).unwrap();
+        assert_eq!(app.get_visible_task_ids(), vec![1, 3]);
+        assert_eq!(app.current_task_id, 3);
+        app.dispatch(Action::PreviousTask).unwrap();
+        assert_eq!(app.current_task_id, 1);
+        assert_eq!(app.get_current_project_tasks().unwrap().len(), 3);
+
+        // Back to the default.
+        for _ in 0..3 {
+            app.dispatch(Action::CycleHidden).unwrap();
+        }
+        let project = app.projects.iter().find(|p| p.is_current).unwrap();
+        assert_eq!(project.view.hide, None);
+        assert_eq!(app.get_visible_task_ids(), vec![1, 2, 3]);
+
+        app.hide_default = HideMode::Finished;
+        assert_eq!(app.get_visible_task_ids(), vec![1, 3]);
+    }
+
+    #[test]
+    fn archive() {
+        let (mut app, files) = build("archive");
+        app.dispatch(Action::CreateTask(String::from("Three")))
+            .unwrap();
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::ToggleTask).unwrap();
+        app.dispatch(Action::NextTask).unwrap();
+        app.dispatch(Action::CancelTask).unwrap();
+
+        app.dispatch(Action::ArchiveDone).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::ConfirmingBatch);
+        app.dispatch(Action::Confirm).unwrap();
+        assert_eq!(current_titles(&app), vec!["Three"]);
+        assert_eq!(app.get_archive_entries().len(), 2);
+        assert!(app.get_archive_entries()[0].task.completed_at.is_some());
+
+        app.dispatch(Action::ShowArchive).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Archive);
+        app.dispatch(Action::NextItem).unwrap();
+        app.dispatch(Action::NextItem).unwrap();
+        app.dispatch(Action::RestoreTask).unwrap();
+        assert_eq!(current_titles(&app), vec!["Three", "Welcome in tuido"]);
+        app.dispatch(Action::Undo).unwrap();
+        assert_eq!(current_titles(&app), vec!["Three"]);
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+
+        // Automatic policy.
+        app.dispatch(Action::CompleteTask).unwrap();
+        app.archive_after_days = Some(14);
+        app.apply_archive_policy(date::now() + 13 * date::DAY)
+            .unwrap();
+        assert_eq!(current_titles(&app), vec!["Three"]);
+        app.apply_archive_policy(date::now() + 14 * date::DAY)
+            .unwrap();
+        assert!(current_titles(&app).is_empty());
+
+        let reloaded = App::from_file_manager(files.get_file_manager()).unwrap();
+        assert_eq!(reloaded.get_archive_entries().len(), 3);
+        assert_eq!(reloaded.next_task_id, 4);
+    }
+
+    #[test]
+    fn backfill_timestamps() {
+        let files = TempFiles::new("backfill");
+        let path = &files.path;
+        fs::write(
+            path,
+            r#"[{"id": 1, "name": "Old", "is_current": true, "tasks": [
+                {"id": 1, "project_id": 1, "state": "Completed", "title": "Done"},
+                {"id": 2, "project_id": 1, "state": "Todo", "title": "Open"}
+            ]}]"#,
+        )
+        .unwrap();
+        let modified = files.get_file_manager().get_modified_time().unwrap();
+
+        let app = App::from_file_manager(files.get_file_manager()).unwrap();
+        let tasks = app.get_current_project_tasks().unwrap();
+        assert_eq!(tasks[0].created_at, modified);
+        assert_eq!(tasks[0].completed_at, Some(modified));
+        assert_eq!(tasks[1].completed_at, None);
+        assert!(fs::read_to_string(path).unwrap().contains("\"created_at\""));
+    }
+
+    #[test]
+    fn navigate_and_toggle() {
+        let (mut app, _files) = build("navigate_and_toggle");
+
+        app.dispatch(Action::LastTask).unwrap();
+        app.dispatch(Action::ToggleTask).unwrap();
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::CancelTask).unwrap();
+
+        let tasks = app.get_current_project_tasks().unwrap();
+        assert_eq!(tasks[0].state, TaskState::Canceled);
+        assert_eq!(tasks[1].state, TaskState::Completed);
+    }
+
+    #[test]
+    fn page_navigation() {
+        let (mut app, _files) = build("page_navigation");
+        for i in 0..20 {
+            app.dispatch(Action::CreateTask(format!("Task {i}")))
+                .unwrap();
+        }
+        app.page_size = 5;
+
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::PageDown).unwrap();
+        assert_eq!(app.get_visible_task_ids()[5], app.current_task_id);
+        app.dispatch(Action::PageUp).unwrap();
+        app.dispatch(Action::PageUp).unwrap();
+        assert_eq!(app.get_visible_task_ids()[0], app.current_task_id);
+        app.dispatch(Action::LastTask).unwrap();
+        app.dispatch(Action::PageDown).unwrap();
+        assert_eq!(app.get_visible_task_ids()[21], app.current_task_id);
+    }
+
+    #[test]
+    fn delete_task() {
+        let (mut app, _files) = build("delete_task");
+
+        app.dispatch(Action::NextTask).unwrap();
+        app.dispatch(Action::DeleteTask).unwrap();
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(current_titles(&app).len(), 2);
+
+        app.dispatch(Action::DeleteTask).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert_eq!(current_titles(&app), vec!["Welcome in tuido"]);
+
+        // The selection stays next to the deleted task.
+        for title in ["Two", "Three", "Four"] {
+            app.add_task(String::from(title));
+        }
+        app.dispatch(Action::LastTask).unwrap();
+        app.dispatch(Action::PreviousTask).unwrap();
+        app.dispatch(Action::DeleteTask).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert_eq!(app.get_current_task().unwrap().title, "Two");
+
+        app.dispatch(Action::FirstTask).unwrap();
+        app.dispatch(Action::DeleteTask).unwrap();
+        app.dispatch(Action::Confirm).unwrap();
+        assert_eq!(app.get_current_task().unwrap().title, "Two");
+        assert_eq!(current_titles(&app), vec!["Two", "Four"]);
+    }
+
+    #[test]
+    fn projects() {
+        let (mut app, _files) = build("projects");
+
+        app.dispatch(Action::NextProject).unwrap();
+        assert_eq!(app.get_current_project_name(), "Other Project");
+
+        app.dispatch(Action::NewProject).unwrap();
+        type_text(&mut app, "Projet 3");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.get_current_project_name(), "Projet 3");
+
+        app.dispatch(Action::SelectProject(1)).unwrap();
+        assert_eq!(app.get_current_project_name(), "Welcome");
+        assert!(app.dispatch(Action::SelectProject(42)).is_err());
+    }
+
+    #[test]
+    fn import_into_empty() {
+        let files = TempFiles::new("import");
+        fs::write(&files.path, "").unwrap();
+        let mut app = App::load_from(files.get_file_manager()).unwrap();
+        assert_eq!(app.import_org("* Work\n** TODO First\n** Second\n"), 1);
+        assert_eq!(app.get_current_project_name(), "Work");
+        assert_eq!(app.get_current_task().unwrap().title, "First");
+        app.dispatch(Action::NextTask).unwrap();
+        assert_eq!(app.get_current_task().unwrap().title, "Second");
+
+        // A file without a current project still shows the first one.
+        app.projects[0].is_current = false;
+        assert_eq!(app.get_current_project_name(), "Work");
+        assert_eq!(app.get_visible_task_ids().len(), 2);
+    }
+
+    #[test]
+    fn rename_without_task() {
+        let (mut app, _files) = build("rename_without_task");
+
+        app.dispatch(Action::NewProject).unwrap();
+        type_text(&mut app, "Empty");
+        app.dispatch(Action::Validate).unwrap();
+        app.dispatch(Action::RenameTask).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+        assert_eq!(app.message.as_deref(), Some("No task to rename"));
+    }
+
+    #[test]
+    fn sidebar() {
+        let (mut app, _files) = build("sidebar");
+
+        app.dispatch(Action::SwitchFocus).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Sidebar);
+        app.dispatch(Action::NextProject).unwrap();
+        assert_eq!(app.get_current_project_name(), "Other Project");
+        app.dispatch(Action::SwitchFocus).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+
+        app.dispatch(Action::SwitchFocus).unwrap();
+        app.dispatch(Action::ToggleSidebar).unwrap();
+        assert!(!app.show_sidebar);
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+    }
+
+    #[test]
+    fn board() {
+        let (mut app, _files) = build("board");
+
+        app.dispatch(Action::ToggleBoard).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Board);
+        app.dispatch(Action::MoveTaskRight).unwrap();
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Completed));
+        app.dispatch(Action::MoveTaskRight).unwrap();
+        app.dispatch(Action::MoveTaskRight).unwrap();
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Canceled));
+
+        // Up and down stay in the column, h and l jump between columns.
+        app.dispatch(Action::NextTask).unwrap();
+        assert_eq!(app.current_task_id, 1);
+        app.dispatch(Action::PreviousColumn).unwrap();
+        assert_eq!(app.current_task_id, 2);
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Todo));
+
+        app.dispatch(Action::AddTask).unwrap();
+        type_text(&mut app, "Card");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Board);
+        app.dispatch(Action::ToggleBoard).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+    }
+
+    #[test]
+    fn filter() {
+        let (mut app, _files) = build("filter");
+
+        app.dispatch(Action::Filter).unwrap();
+        type_text(&mut app, "love (");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Filtering);
+        assert!(app.filter_error.is_some());
+
+        app.dispatch(Action::DeleteChar).unwrap();
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+        assert_eq!(app.get_visible_task_ids(), vec![2]);
+        assert_eq!(app.current_task_id, 2);
+    }
+
+    #[test]
+    fn palette() {
+        let (mut app, _files) = build("palette");
+
+        app.dispatch(Action::Palette).unwrap();
+        type_text(&mut app, "switch other");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Main);
+        assert_eq!(app.get_current_project_name(), "Other Project");
+
+        app.dispatch(Action::Palette).unwrap();
+        type_text(&mut app, "task");
+        app.dispatch(Action::NextItem).unwrap();
+        let expected = palette::get_matches(&app, "task").remove(1).action;
+        app.dispatch(Action::PreviousItem).unwrap();
+        app.dispatch(Action::NextItem).unwrap();
+        assert_eq!(
+            palette::get_matches(&app, &app.cursor_manager.string)[app.palette_index].action,
+            expected
+        );
+    }
+
+    #[test]
+    fn export_next_to_data() {
+        let (mut app, files) = build("export");
+        app.dispatch(Action::ExportOrg).unwrap();
+        let path = files.path.with_extension("org");
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("* Welcome\n"));
+        assert_eq!(app.message, Some(format!("Exported to {}", path.display())));
+    }
+
+    #[test]
+    fn search() {
+        let (mut app, _files) = build("search");
+
+        app.dispatch(Action::Search).unwrap();
+        assert_eq!(app.screen_mode, ScreenMode::Searching);
+        type_text(&mut app, "MUTI");
+        assert_eq!(app.get_current_project_name(), "Other Project");
+        app.dispatch(Action::Abort).unwrap();
+        assert_eq!(app.get_current_project_name(), "Welcome");
+        assert!(app.search.is_empty());
+
+        app.dispatch(Action::Search).unwrap();
+        type_text(&mut app, "tuido");
+        app.dispatch(Action::Validate).unwrap();
+        assert_eq!(app.get_search_matches(), vec![(1, 1), (1, 2)]);
+        assert_eq!(app.current_task_id, 1);
+        app.dispatch(Action::NextMatch).unwrap();
+        assert_eq!(app.current_task_id, 2);
+        app.dispatch(Action::NextMatch).unwrap();
+        assert_eq!(app.current_task_id, 1);
+        app.dispatch(Action::PreviousMatch).unwrap();
+        assert_eq!(app.current_task_id, 2);
+
+        app.dispatch(Action::Search).unwrap();
+        type_text(&mut app, "nothing");
+        app.dispatch(Action::Validate).unwrap();
+        assert!(app.search.is_empty());
+        assert!(app.message.is_some());
+    }
+
+    #[test]
+    fn quit_saves() {
+        let (mut app, _files) = build("quit_saves");
+
+        app.dispatch(Action::CreateTask(String::from("Persisted")))
+            .unwrap();
+        app.dispatch(Action::Quit).unwrap();
+        assert!(app.should_quit);
+
+        let app = App::from_file_manager(app.file_manager).unwrap();
+        assert_eq!(current_titles(&app).last().unwrap(), "Persisted");
+    }
+
+    #[test]
+    fn load_does_not_write() {
+        let files = TempFiles::new("load");
+        let path = &files.path;
+        fs::write(path, "").unwrap();
+        let app = App::load_from(files.get_file_manager()).unwrap();
+        assert!(app.projects.is_empty());
+        assert_eq!(fs::read_to_string(path).unwrap(), "");
+
+        fs::write(path, "{ broken").unwrap();
+        assert!(App::load_from(files.get_file_manager()).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "{ broken");
+
+        // Nothing flagged as current, as after an import: the first project.
+        let mut project = Project::new(4, String::from("Imported"));
+        project.add_task(7, String::from("Task"));
+        fs::write(path, serde_json::to_string(&vec![project]).unwrap()).unwrap();
+        let mut app = App::load_from(files.get_file_manager()).unwrap();
+        assert_eq!(app.current_project_id, 4);
+        let previous_project_id = app.current_project_id;
+        app.dispatch(Action::SelectTask(7)).unwrap();
+        app.dispatch(Action::CompleteTask).unwrap();
+        app.dispatch(Action::SelectProject(previous_project_id))
+            .unwrap();
+    }
 }
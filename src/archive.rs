@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+use crate::{
+    comps::{Project, Task, TaskState},
+    date::DAY,
+};
+
+// Archive stuff
+//
+// Finished tasks moved out of the data file into `tasks.archive.json`, next
+// to it. They keep their id, project and completion time, so they can be
+// browsed per project and restored.
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArchivedTask {
+    pub archived_at: u64,
+    pub task: Task,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Archive {
+    pub entries: Vec<ArchivedTask>,
+}
+
+impl Archive {
+    pub fn add(&mut self, tasks: Vec<Task>, now: u64) {
+        self.entries
+            .extend(tasks.into_iter().map(|task| ArchivedTask {
+                archived_at: now,
+                task,
+            }));
+    }
+
+    // Latest archived first.
+    pub fn get_project_entries(&self, project_id: u32) -> Vec<&ArchivedTask> {
+        let mut entries: Vec<&ArchivedTask> = self
+            .entries
+            .iter()
+            .filter(|e| e.task.get_project_id() == project_id)
+            .collect();
+        entries.reverse();
+        entries.sort_by_key(|e| Reverse(e.archived_at));
+        entries
+    }
+
+    pub fn take(&mut self, project_id: u32, task_id: u32) -> Option<Task> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.task.get_project_id() == project_id && e.task.id == task_id)?;
+        Some(self.entries.remove(index).task)
+    }
+
+    pub fn get_max_id(&self) -> Option<u32> {
+        self.entries.iter().map(|e| e.task.id).max()
+    }
+}
+
+// Finished tasks of `project` completed or canceled at least `days` days
+// before `now`.
+pub fn get_expired_ids(project: &Project, days: u64, now: u64) -> Vec<u32> {
+    project
+        .tasks
+        .iter()
+        .filter(|t| t.state != TaskState::Todo)
+        .filter(|t| t.completed_at.is_some_and(|at| at + days * DAY <= now))
+        .map(|t| t.id)
+        .collect()
+}
+
+// Tests
+
+#[cfg(test)]
+mod archive_test {
+    use super::*;
+
+    #[test]
+    fn add_and_take() {
+        let mut archive = Archive::default();
+        archive.add(
+            vec![
+                Task::new(1, 1, String::from("One")),
+                Task::new(2, 2, String::from("Two")),
+            ],
+            10,
+        );
+        archive.add(vec![Task::new(3, 1, String::from("Three"))], 20);
+
+        let titles: Vec<&str> = archive
+            .get_project_entries(1)
+            .iter()
+            .map(|e| e.task.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Three", "One"]);
+        assert_eq!(archive.get_max_id(), Some(3));
+
+        assert!(archive.take(2, 1).is_none());
+        assert_eq!(archive.take(1, 1).unwrap().title, "One");
+        assert_eq!(archive.entries.len(), 2);
+    }
+
+    #[test]
+    fn expired() {
+        let mut project = Project::new(1, String::from("Project"));
+        for id in 1..=4 {
+            project.add_task(id, format!("Task {id}"));
+        }
+        project.tasks[0].state = TaskState::Completed;
+        project.tasks[0].completed_at = Some(0);
+        project.tasks[1].state = TaskState::Canceled;
+        project.tasks[1].completed_at = Some(10 * DAY);
+        project.tasks[2].state = TaskState::Completed;
+        project.tasks[3].completed_at = Some(0);
+
+        assert_eq!(get_expired_ids(&project, 14, 14 * DAY), vec![1]);
+        assert_eq!(get_expired_ids(&project, 14, 24 * DAY), vec![1, 2]);
+    }
+}
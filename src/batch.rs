@@ -0,0 +1,176 @@
+use crate::comps::{Project, Task, TaskState};
+
+// Batch stuff
+//
+// Operations applied at once to the tasks marked in the current project.
+// `App` asks for a single confirmation, then applies the whole batch as one
+// undo step.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    SetState(TaskState),
+    Delete,
+    // To the archive store, see `App::archive`.
+    Archive,
+    // Id of the target project.
+    Move(u32),
+    // Tag names, without the `#`.
+    Tag(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    pub project_id: u32,
+    pub task_ids: Vec<u32>,
+    pub operation: BatchOperation,
+}
+
+impl Batch {
+    pub fn get_question(&self, projects: &[Project]) -> String {
+        let count = match self.task_ids.len() {
+            1 => String::from("1 task"),
+            n => format!("{n} tasks"),
+        };
+        match &self.operation {
+            BatchOperation::SetState(TaskState::Todo) => format!("Reopen {count}?"),
+            BatchOperation::SetState(TaskState::Completed) => format!("Complete {count}?"),
+            BatchOperation::SetState(TaskState::Canceled) => format!("Cancel {count}?"),
+            BatchOperation::Delete => format!("Delete {count}?"),
+            BatchOperation::Archive => format!("Archive {count}?"),
+            BatchOperation::Move(id) => {
+                let name = projects
+                    .iter()
+                    .find(|p| p.id == *id)
+                    .map_or("?", |p| p.name.as_str());
+                format!("Move {count} to {name}?")
+            }
+            BatchOperation::Tag(tags) => {
+                let tags: Vec<String> = tags.iter().map(|t| format!("#{t}")).collect();
+                format!("Tag {count} with {}?", tags.join(" "))
+            }
+        }
+    }
+
+    // Returns the tasks taken out of the projects: the deleted or archived
+    // ones.
+    pub fn apply(&self, projects: &mut [Project]) -> Vec<Task> {
+        let Some(project) = projects.iter_mut().find(|p| p.id == self.project_id) else {
+            return vec![];
+        };
+        match &self.operation {
+            BatchOperation::SetState(state) => {
+                for id in &self.task_ids {
+                    project.set_task_state(*id, *state);
+                }
+            }
+            BatchOperation::Delete | BatchOperation::Archive => {
+                return project.take_tasks(&self.task_ids);
+            }
+            BatchOperation::Move(target_id) => {
+                let tasks = project.take_tasks(&self.task_ids);
+                if let Some(target) = projects.iter_mut().find(|p| p.id == *target_id) {
+                    for task in tasks {
+                        target.insert_task(task);
+                    }
+                }
+            }
+            BatchOperation::Tag(tags) => {
+                for task in &mut project.tasks {
+                    if !self.task_ids.contains(&task.id) {
+                        continue;
+                    }
+                    let missing: Vec<String> = tags
+                        .iter()
+                        .filter(|tag| !task.tags().contains(&tag.as_str()))
+                        .map(|tag| format!(" #{tag}"))
+                        .collect();
+                    task.rename(format!("{}{}", task.title, missing.concat()));
+                }
+            }
+        }
+        vec![]
+    }
+}
+
+// Tags typed in the tag prompt, with or without their `#`.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for word in input.split_whitespace() {
+        let tag = word.trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Tests
+
+#[cfg(test)]
+mod batch_test {
+    use super::*;
+
+    fn build_projects() -> Vec<Project> {
+        let mut first = Project::new(1, String::from("First"));
+        first.add_task(1, String::from("One"));
+        first.add_task(2, String::from("Two #done"));
+        first.add_task(3, String::from("Three"));
+        vec![first, Project::new(2, String::from("Second"))]
+    }
+
+    fn apply(projects: &mut [Project], operation: BatchOperation) {
+        let batch = Batch {
+            project_id: 1,
+            task_ids: vec![1, 2],
+            operation,
+        };
+        batch.apply(projects);
+    }
+
+    #[test]
+    fn operations() {
+        let mut projects = build_projects();
+
+        apply(&mut projects, BatchOperation::SetState(TaskState::Canceled));
+        assert_eq!(projects[0].tasks[1].state, TaskState::Canceled);
+        assert_eq!(projects[0].tasks[2].state, TaskState::Todo);
+
+        apply(
+            &mut projects,
+            BatchOperation::Tag(parse_tags("#done urgent done")),
+        );
+        assert_eq!(projects[0].tasks[0].title, "One #done #urgent");
+        assert_eq!(projects[0].tasks[1].title, "Two #done #urgent");
+
+        apply(&mut projects, BatchOperation::Move(2));
+        assert_eq!(projects[0].tasks.len(), 1);
+        assert_eq!(projects[1].tasks.len(), 2);
+
+        let mut projects = build_projects();
+        apply(&mut projects, BatchOperation::Delete);
+        assert_eq!(projects[0].tasks[0].title, "Three");
+
+        let mut projects = build_projects();
+        let batch = Batch {
+            project_id: 1,
+            task_ids: vec![2],
+            operation: BatchOperation::Archive,
+        };
+        let archived = batch.apply(&mut projects);
+        assert_eq!(archived[0].title, "Two #done");
+        assert_eq!(projects[0].tasks.len(), 2);
+    }
+
+    #[test]
+    fn question() {
+        let batch = Batch {
+            project_id: 1,
+            task_ids: vec![1, 2],
+            operation: BatchOperation::Move(2),
+        };
+        assert_eq!(
+            batch.get_question(&build_projects()),
+            "Move 2 tasks to Second?"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::{error::Error, fs};
+
+use crate::{
+    action::Action,
+    app::App,
+    comps::TaskState,
+    output::{self, Format, ProjectRecord, TaskRecord},
+    query::Query,
+    report,
+};
+
+// CLI stuff
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_NO_MATCH: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+const USAGE: &str = "Usage:
+    tuido                                   Start the TUI
+    tuido add <project> <title>             Add a task to a project
+    tuido done <id>                         Mark a task as completed
+    tuido cancel <id>                       Mark a task as canceled
+    tuido list [--project P] [--state S] [--format F] [query]
+                                            List tasks (S: todo, completed, canceled)
+    tuido projects [--format F]             List projects
+    tuido import-org <file>                 Import projects and tasks from an org file
+    tuido export-org [file]                 Export projects and tasks as org (stdout if no file)
+    tuido report [file]                     Write an HTML report of all projects (stdout if no file)
+
+Formats (F): plain (default), tsv, json, jsonl.
+Queries: state:todo project:Backend tag:review id>10 \"flaky\", combined with
+and, or, not and parentheses.
+Exit codes: 0 on success, 1 when a list command matches nothing, 2 on errors.";
+
+pub fn run(args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let result = match args.first().map(String::as_str) {
+        Some("list") => return list(&args[1..]),
+        Some("projects") => return projects(&args[1..]),
+        Some("add") => add(&args[1..]),
+        Some("done") => done(&args[1..]),
+        Some("cancel") => cancel(&args[1..]),
+        Some("import-org") => import_org(&args[1..]),
+        Some("export-org") => export_org(&args[1..]),
+        Some("report") => write_report(&args[1..]),
+        Some("help" | "-h" | "--help") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command '{command}'\n\n{USAGE}").into()),
+        None => Err(USAGE.into()),
+    };
+    result.map(|_| EXIT_SUCCESS)
+}
+
+fn add(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [project_name, title @ ..] = args else {
+        return Err(format!("add expects a project and a title\n\n{USAGE}").into());
+    };
+    if title.is_empty() {
+        return Err(format!("add expects a project and a title\n\n{USAGE}").into());
+    }
+    let mut app = App::load()?;
+    let project_id = app
+        .find_project(project_name)
+        .ok_or_else(|| format!("no project named '{project_name}'"))?;
+
+    let previous_project_id = app.current_project_id;
+    app.dispatch(Action::SelectProject(project_id))?;
+    app.dispatch(Action::CreateTask(title.join(" ")))?;
+    let task_id = app.current_task_id;
+    restore_project(&mut app, previous_project_id)?;
+    app.save_file()?;
+
+    println!("Added task {task_id} to {project_name}");
+    Ok(())
+}
+
+fn done(args: &[String]) -> Result<(), Box<dyn Error>> {
+    update_task(args, "done", Action::CompleteTask)
+}
+
+fn cancel(args: &[String]) -> Result<(), Box<dyn Error>> {
+    update_task(args, "cancel", Action::CancelTask)
+}
+
+fn update_task(args: &[String], command: &str, update: Action) -> Result<(), Box<dyn Error>> {
+    let [id] = args else {
+        return Err(format!("{command} expects a task id\n\n{USAGE}").into());
+    };
+    let id: u32 = id.parse().map_err(|_| format!("invalid task id '{id}'"))?;
+    let mut app = App::load()?;
+
+    let previous_project_id = app.current_project_id;
+    app.dispatch(Action::SelectTask(id))?;
+    app.dispatch(update)?;
+    restore_project(&mut app, previous_project_id)?;
+    app.save_file()?;
+    Ok(())
+}
+
+// Selects back the project the TUI opens on, if there was one.
+fn restore_project(app: &mut App, project_id: u32) -> Result<(), Box<dyn Error>> {
+    if app.projects.iter().any(|p| p.id == project_id) {
+        app.dispatch(Action::SelectProject(project_id))?;
+    }
+    Ok(())
+}
+
+fn list(args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let mut project_filter = None;
+    let mut state_filter = None;
+    let mut format = Format::Plain;
+    let mut query = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--project" | "-p" => project_filter = Some(expect_value(args.next(), arg)?),
+            "--state" | "-s" => state_filter = Some(parse_state(expect_value(args.next(), arg)?)?),
+            "--format" | "-f" => format = Format::parse(expect_value(args.next(), arg)?)?,
+            _ if arg.starts_with('-') => {
+                return Err(format!("unexpected argument '{arg}'\n\n{USAGE}").into())
+            }
+            _ => query.push(arg.as_str()),
+        }
+    }
+    let query = Query::parse(&query.join(" ")).map_err(|e| format!("invalid query: {e}"))?;
+
+    let app = App::load()?;
+    let project_id = match project_filter {
+        Some(name) => Some(
+            app.find_project(name)
+                .ok_or_else(|| format!("no project named '{name}'"))?,
+        ),
+        None => None,
+    };
+
+    let records: Vec<TaskRecord> = app
+        .projects
+        .iter()
+        .filter(|project| project_id.is_none_or(|id| id == project.id))
+        .flat_map(|project| {
+            project
+                .tasks
+                .iter()
+                .filter(|task| state_filter.is_none_or(|state| state == task.state))
+                .filter(|task| query.matches(project, task))
+                .map(move |task| TaskRecord::new(project, task))
+        })
+        .collect();
+
+    print!("{}", output::format_tasks(&records, format)?);
+    Ok(get_exit_code(records.len()))
+}
+
+fn projects(args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let mut format = Format::Plain;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" | "-f" => format = Format::parse(expect_value(args.next(), arg)?)?,
+            _ => return Err(format!("unexpected argument '{arg}'\n\n{USAGE}").into()),
+        }
+    }
+
+    let app = App::load()?;
+    let records: Vec<ProjectRecord> = app.projects.iter().map(ProjectRecord::new).collect();
+
+    print!("{}", output::format_projects(&records, format)?);
+    Ok(get_exit_code(records.len()))
+}
+
+fn import_org(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else {
+        return Err(format!("import-org expects a file\n\n{USAGE}").into());
+    };
+    let content = fs::read_to_string(path)?;
+    let mut app = App::load()?;
+    let count = app.import_org(&content);
+    app.save_file()?;
+    println!("Imported {count} project(s) from {path}");
+    Ok(())
+}
+
+fn export_org(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let app = App::load()?;
+    match args {
+        [] => print!("{}", app.export_org()),
+        [path] => fs::write(path, app.export_org())?,
+        _ => return Err(format!("export-org expects at most one file\n\n{USAGE}").into()),
+    }
+    Ok(())
+}
+
+fn write_report(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let app = App::load()?;
+    let html = report::to_html(&app.projects);
+    match args {
+        [] => print!("{html}"),
+        [path] => fs::write(path, html)?,
+        _ => return Err(format!("report expects at most one file\n\n{USAGE}").into()),
+    }
+    Ok(())
+}
+
+fn expect_value<'a>(value: Option<&'a String>, option: &str) -> Result<&'a str, Box<dyn Error>> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| format!("{option} expects a value").into())
+}
+
+fn parse_state(state: &str) -> Result<TaskState, Box<dyn Error>> {
+    match state.to_lowercase().as_str() {
+        "todo" => Ok(TaskState::Todo),
+        "completed" | "done" => Ok(TaskState::Completed),
+        "canceled" | "cancelled" => Ok(TaskState::Canceled),
+        _ => Err(format!("unknown state '{state}' (expected todo, completed or canceled)").into()),
+    }
+}
+
+fn get_exit_code(matches: usize) -> i32 {
+    if matches == 0 {
+        EXIT_NO_MATCH
+    } else {
+        EXIT_SUCCESS
+    }
+}
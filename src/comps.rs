@@ -4,73 +4,260 @@ use std::{
     cmp::Ordering,
     env,
     error::Error,
-    fs,
+    fs, io,
+    ops::Range,
     path::{Path, PathBuf},
+    time::UNIX_EPOCH,
     vec,
 };
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{archive::Archive, date};
+use unicode_width::UnicodeWidthStr;
 
 // CursorManager stuff
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CursorDirection {
     Left,
     Right,
+    WordLeft,
+    WordRight,
+    Start,
+    End,
 }
 
 pub struct CursorManager {
     pub string: String,
-    pub cursor_position: u16,
+    // Byte index into `string`, always on a grapheme boundary.
+    pub cursor: usize,
+    // Last killed text, kept across prompts for `yank`.
+    pub yank_buffer: String,
+    completion: Option<Completion>,
+}
+
+// Tab completion in progress: `text` was put in place of `prefix` at
+// `start`, pressing Tab again cycles through `matches` then back to
+// `prefix`.
+struct Completion {
+    start: usize,
+    prefix: String,
+    matches: Vec<String>,
+    index: usize,
 }
 
 impl CursorManager {
     pub fn new() -> Self {
         CursorManager {
             string: String::new(),
-            cursor_position: 0,
+            cursor: 0,
+            yank_buffer: String::new(),
+            completion: None,
         }
     }
 
     pub fn insert(&mut self, c: char) {
-        self.string.insert(self.cursor_position as usize, c);
-        self.cursor_position += 1;
+        self.string.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.snap_cursor(true);
+    }
+
+    // Pasted text is a single line: line breaks and tabs become spaces and
+    // other control characters are dropped.
+    pub fn insert_str(&mut self, s: &str) {
+        let s: String = s
+            .chars()
+            .filter_map(|c| match c {
+                '\r' | '\n' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+        self.string.insert_str(self.cursor, &s);
+        self.cursor += s.len();
+        self.snap_cursor(true);
     }
 
+    // Deletes the whole grapheme before the cursor (an emoji with its
+    // modifiers, a letter with its accents...).
     pub fn delete(&mut self) {
-        if self.cursor_position > 0 {
-            self.string.remove(self.cursor_position as usize - 1);
-            self.cursor_position -= 1;
+        self.delete_to(self.get_target(CursorDirection::Left));
+    }
+
+    pub fn delete_next(&mut self) {
+        self.delete_to(self.get_target(CursorDirection::Right));
+    }
+
+    // Deletes up to where `dir` would move the cursor, keeping the text for
+    // `yank`.
+    pub fn kill(&mut self, dir: CursorDirection) {
+        let target = self.get_target(dir);
+        self.cut(self.cursor.min(target)..self.cursor.max(target));
+    }
+
+    // `range` must start and end on grapheme boundaries.
+    pub fn cut(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.yank_buffer = self.string.drain(range.clone()).collect();
+            self.cursor = range.start;
+            self.snap_cursor(false);
         }
     }
 
+    pub fn yank(&mut self) {
+        let s = self.yank_buffer.clone();
+        self.insert_str(&s);
+    }
+
     pub fn move_cursor(&mut self, dir: CursorDirection) {
-        match dir {
-            CursorDirection::Left => {
-                if self.cursor_position > 0 {
-                    self.cursor_position -= 1;
-                }
-            }
-            CursorDirection::Right => {
-                if self.cursor_position < self.string.len() as u16 {
-                    self.cursor_position += 1;
+        self.cursor = self.get_target(dir);
+    }
+
+    // Completes the text before the cursor with the first candidate starting
+    // with it (ignoring case). The text is taken from the earliest word start
+    // that has matches, so multi word candidates like project names complete
+    // too. Returns false when nothing matches.
+    pub fn complete(&mut self, candidates: &[String]) -> bool {
+        let completion = match self.completion.take() {
+            Some(completion) if self.is_completing(&completion) => Some(completion),
+            _ => self.start_completion(candidates),
+        };
+        let Some(mut completion) = completion else {
+            return false;
+        };
+
+        let old_end = self.cursor;
+        completion.index = (completion.index + 1) % (completion.matches.len() + 1);
+        let text = completion
+            .matches
+            .get(completion.index)
+            .unwrap_or(&completion.prefix);
+        self.string.replace_range(completion.start..old_end, text);
+        self.cursor = completion.start + text.len();
+        self.completion = Some(completion);
+        true
+    }
+
+    // The cursor is still right after the text the last Tab inserted.
+    fn is_completing(&self, completion: &Completion) -> bool {
+        let text = completion
+            .matches
+            .get(completion.index)
+            .unwrap_or(&completion.prefix);
+        self.cursor >= completion.start
+            && self.string.get(completion.start..self.cursor) == Some(text.as_str())
+    }
+
+    fn start_completion(&self, candidates: &[String]) -> Option<Completion> {
+        let before = &self.string[..self.cursor];
+        let word_starts: Vec<usize> = before
+            .grapheme_indices(true)
+            .filter(|(i, g)| {
+                !g.chars().all(char::is_whitespace)
+                    && before[..*i]
+                        .graphemes(true)
+                        .next_back()
+                        .is_none_or(|p| p.chars().all(char::is_whitespace))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        word_starts.into_iter().find_map(|start| {
+            let prefix = &before[start..];
+            let lower = prefix.to_lowercase();
+            let mut matches: Vec<String> = vec![];
+            for candidate in candidates {
+                let candidate_lower = candidate.to_lowercase();
+                if candidate_lower.starts_with(&lower)
+                    && candidate_lower != lower
+                    && !matches.contains(candidate)
+                {
+                    matches.push(candidate.clone());
                 }
             }
+            (!matches.is_empty()).then(|| Completion {
+                start,
+                prefix: prefix.to_string(),
+                // Starts on the prefix, the first Tab moves to the first match.
+                index: matches.len(),
+                matches,
+            })
+        })
+    }
+
+    // Words are runs of letters and digits, as in readline.
+    fn get_target(&self, dir: CursorDirection) -> usize {
+        let is_word = |g: &str| g.chars().next().is_some_and(char::is_alphanumeric);
+        let before = self.string[..self.cursor].grapheme_indices(true).rev();
+        let after = self.string[self.cursor..]
+            .grapheme_indices(true)
+            .map(|(i, g)| (self.cursor + i, g));
+        match dir {
+            CursorDirection::Left => before.map(|(i, _)| i).next().unwrap_or(0),
+            CursorDirection::Right => after
+                .map(|(i, g)| i + g.len())
+                .next()
+                .unwrap_or(self.string.len()),
+            CursorDirection::WordLeft => before
+                .skip_while(|(_, g)| !is_word(g))
+                .take_while(|(_, g)| is_word(g))
+                .last()
+                .map_or(0, |(i, _)| i),
+            CursorDirection::WordRight => after
+                .skip_while(|(_, g)| !is_word(g))
+                .take_while(|(_, g)| is_word(g))
+                .last()
+                .map_or(self.string.len(), |(i, g)| i + g.len()),
+            CursorDirection::Start => 0,
+            CursorDirection::End => self.string.len(),
         }
     }
 
+    fn delete_to(&mut self, target: usize) {
+        let start = self.cursor.min(target);
+        self.string.drain(start..self.cursor.max(target));
+        self.cursor = start;
+        self.snap_cursor(false);
+    }
+
+    // Terminal columns between the start of the input and the cursor, with
+    // wide characters (CJK, emoji) counting twice.
+    pub fn get_display_position(&self) -> u16 {
+        self.string[..self.cursor].width() as u16
+    }
+
     pub fn validate(&mut self) -> String {
         let s = self.string.clone();
         self.string.clear();
-        self.cursor_position = 0;
+        self.cursor = 0;
         s
     }
 
     pub fn set_string(&mut self, s: String) {
         self.string = s;
-        self.cursor_position = self.string.len() as u16;
+        self.cursor = self.string.len();
     }
 
     pub fn clear(&mut self) {
         self.string.clear();
-        self.cursor_position = 0;
+        self.cursor = 0;
+    }
+
+    // Typing a combining character, or deleting what separated two
+    // characters, can merge graphemes around the cursor. Move it to the end
+    // (`forward`) or the start of the grapheme it ended up in.
+    fn snap_cursor(&mut self, forward: bool) {
+        let mut start = 0;
+        for grapheme in self.string.graphemes(true) {
+            let end = start + grapheme.len();
+            if end > self.cursor {
+                if start < self.cursor {
+                    self.cursor = if forward { end } else { start };
+                }
+                return;
+            }
+            start = end;
+        }
     }
 }
 
@@ -88,15 +275,37 @@ impl FileManager {
         Ok(file_manager)
     }
 
-    pub fn get_file_path(&mut self) -> Result<(), Box<dyn Error>> {
-        let mut file_path = PathBuf::from(env::var("HOME").unwrap());
-
-        file_path.push(Path::new(".tuido"));
+    pub fn with_path(file_path: PathBuf) -> Result<Self, Box<dyn Error>> {
         if !file_path.exists() {
-            fs::create_dir(file_path.clone())?;
+            fs::File::create(&file_path)?;
         }
+        Ok(FileManager { file_path })
+    }
+
+    // The default data file, without creating anything: the command line
+    // must not write when it only reads.
+    pub fn locate() -> Result<Self, Box<dyn Error>> {
+        Ok(FileManager {
+            file_path: FileManager::get_default_path()?,
+        })
+    }
 
+    pub fn get_default_path() -> Result<PathBuf, Box<dyn Error>> {
+        let mut file_path = PathBuf::from(env::var("HOME").map_err(|_| "$HOME is not set")?);
+        file_path.push(Path::new(".tuido"));
         file_path.push(Path::new("tasks.json"));
+        Ok(file_path)
+    }
+
+    pub fn get_file_path(&mut self) -> Result<(), Box<dyn Error>> {
+        let file_path = FileManager::get_default_path()?;
+
+        if let Some(dir) = file_path.parent() {
+            if !dir.exists() {
+                fs::create_dir(dir)?;
+            }
+        }
+
         if !file_path.exists() {
             fs::File::create(file_path.clone())?;
         }
@@ -106,78 +315,361 @@ impl FileManager {
     }
 
     pub fn save_file(&mut self, projects: &Vec<Project>) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = self.file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
         let file = fs::File::create(self.file_path.clone())?;
         to_writer_pretty(file, projects)?;
         Ok(())
     }
 
+    // The add task history lives next to the data file
+    // (`tasks.json` -> `tasks.history.json`).
+    pub fn get_history_path(&self) -> PathBuf {
+        self.file_path.with_extension("history.json")
+    }
+
+    pub fn save_history(&mut self, entries: &[String]) -> Result<(), Box<dyn Error>> {
+        let file = fs::File::create(self.get_history_path())?;
+        to_writer_pretty(file, entries)?;
+        Ok(())
+    }
+
+    pub fn open_history(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        match fs::File::open(self.get_history_path()) {
+            Ok(file) => Ok(from_reader(file).unwrap_or_default()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Archived tasks also live next to the data file
+    // (`tasks.json` -> `tasks.archive.json`).
+    pub fn get_archive_path(&self) -> PathBuf {
+        self.file_path.with_extension("archive.json")
+    }
+
+    // Exports from the TUI go next to the data file rather than in whatever
+    // directory tuido was started from.
+    pub fn get_export_path(&self, extension: &str) -> PathBuf {
+        self.file_path.with_extension(extension)
+    }
+
+    pub fn save_archive(&mut self, archive: &Archive) -> Result<(), Box<dyn Error>> {
+        let file = fs::File::create(self.get_archive_path())?;
+        to_writer_pretty(file, archive)?;
+        Ok(())
+    }
+
+    // Unlike the history, an unreadable archive is an error: saving over it
+    // would lose the archived tasks.
+    pub fn open_archive(&mut self) -> Result<Archive, Box<dyn Error>> {
+        match fs::File::open(self.get_archive_path()) {
+            Ok(file) => from_reader(file)
+                .map_err(|e| format!("{}: {e}", self.get_archive_path().display()).into()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Archive::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Last write of the data file, the backfill time of old tasks.
+    pub fn get_modified_time(&self) -> Option<u64> {
+        let modified = fs::metadata(&self.file_path).ok()?.modified().ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+    }
+
     pub fn open_file(&mut self) -> Result<Vec<Project>, Box<dyn Error>> {
         let file = fs::File::open(self.file_path.clone())?;
-        let projects = from_reader(file);
+        match from_reader(file) {
+            Ok(projects) => Ok(projects),
+            Err(_) => Ok(vec![]),
+        }
+    }
 
-        if projects.is_ok() {
-            return Ok(projects.unwrap());
-        } else {
+    // Strict version of `open_file` for the command line: a missing or
+    // empty file has no projects, an unreadable one is an error.
+    pub fn read_projects(&self) -> Result<Vec<Project>, Box<dyn Error>> {
+        let content = match fs::read_to_string(&self.file_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        if content.trim().is_empty() {
             return Ok(vec![]);
         }
+        serde_json::from_str(&content)
+            .map_err(|e| format!("{}: {e}", self.file_path.display()).into())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskState {
     Todo,
     Completed,
     Canceled,
 }
 
+impl TaskState {
+    // Board columns follow this order.
+    pub const ALL: &'static [TaskState] =
+        &[TaskState::Todo, TaskState::Completed, TaskState::Canceled];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TaskState::Todo => "Todo",
+            TaskState::Completed => "Completed",
+            TaskState::Canceled => "Canceled",
+        }
+    }
+}
+
 // Task stuff
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: u32,
     project_id: u32,
     pub state: TaskState,
     pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    // Timestamps, see `date`. Files written before they existed get them
+    // from `backfill`.
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub modified_at: u64,
+    // When the task was completed or canceled, see `set_state`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
 }
 
 impl Task {
     pub fn new(id: u32, project_id: u32, title: String) -> Self {
+        let now = date::now();
         Task {
             id,
             project_id,
             state: TaskState::Todo,
             title,
+            notes: String::new(),
+            created_at: now,
+            modified_at: now,
+            completed_at: None,
+        }
+    }
+
+    // Fills the timestamps missing from older files with `timestamp`, the
+    // best known time the task existed at.
+    pub fn backfill(&mut self, timestamp: u64) {
+        if self.created_at == 0 {
+            self.created_at = timestamp;
+        }
+        if self.modified_at == 0 {
+            self.modified_at = self.created_at.max(timestamp);
+        }
+        if self.state != TaskState::Todo && self.completed_at.is_none() {
+            self.completed_at = Some(self.modified_at);
+        }
+    }
+
+    pub fn touch(&mut self) {
+        self.modified_at = date::now();
+    }
+
+    pub fn get_project_id(&self) -> u32 {
+        self.project_id
+    }
+
+    // Every state change goes through here to keep `completed_at` in step.
+    pub fn set_state(&mut self, state: TaskState) {
+        if state != self.state {
+            let now = date::now();
+            self.completed_at = (state != TaskState::Todo).then_some(now);
+            self.modified_at = now;
+            self.state = state;
         }
     }
 
     pub fn toggle_state(&mut self) {
         match self.state {
-            TaskState::Todo => self.state = TaskState::Completed,
-            TaskState::Canceled => self.state = TaskState::Todo,
-            TaskState::Completed => self.state = TaskState::Todo,
+            TaskState::Todo => self.set_state(TaskState::Completed),
+            TaskState::Canceled | TaskState::Completed => self.set_state(TaskState::Todo),
         }
     }
 
     pub fn cancel(&mut self) {
-        match self.state {
-            TaskState::Canceled => {}
-            _ => self.state = TaskState::Canceled,
-        }
+        self.set_state(TaskState::Canceled);
+    }
+
+    pub fn complete(&mut self) {
+        self.set_state(TaskState::Completed);
     }
 
     pub fn rename(&mut self, new_title: String) {
-        self.title = new_title.clone();
+        if new_title != self.title {
+            self.title = new_title.clone();
+            self.touch();
+        }
+    }
+
+    pub fn tags(&self) -> Vec<&str> {
+        self.title
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+// View stuff
+//
+// How a project orders its task list. It is saved with the project, so each
+// one keeps its own order.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    // Order of the data file, new tasks last.
+    #[default]
+    Manual,
+    Title,
+    State,
+    // Oldest first, by `created_at`. Ids are not the creation order for
+    // tasks numbered by an import.
+    Created,
+    Id,
+}
+
+impl SortKey {
+    pub const ALL: &'static [SortKey] = &[
+        SortKey::Manual,
+        SortKey::Title,
+        SortKey::State,
+        SortKey::Created,
+        SortKey::Id,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SortKey::Manual => "manual",
+            SortKey::Title => "title",
+            SortKey::State => "state",
+            SortKey::Created => "creation date",
+            SortKey::Id => "id",
+        }
+    }
+
+    pub fn next(&self) -> SortKey {
+        let index = SortKey::ALL.iter().position(|k| k == self).unwrap();
+        SortKey::ALL[(index + 1) % SortKey::ALL.len()]
+    }
+
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            SortKey::Manual => Ordering::Equal,
+            SortKey::Title => a
+                .title
+                .to_lowercase()
+                .cmp(&b.title.to_lowercase())
+                .then_with(|| a.title.cmp(&b.title)),
+            SortKey::State => get_state_index(a.state).cmp(&get_state_index(b.state)),
+            SortKey::Created => a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)),
+            SortKey::Id => a.id.cmp(&b.id),
+        }
+    }
+}
+
+fn get_state_index(state: TaskState) -> usize {
+    TaskState::ALL.iter().position(|s| *s == state).unwrap()
+}
+
+// Finished tasks left out of the list and the board. They still count in
+// the statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HideMode {
+    #[default]
+    None,
+    Completed,
+    Canceled,
+    Finished,
+}
+
+impl HideMode {
+    pub const ALL: &'static [HideMode] = &[
+        HideMode::None,
+        HideMode::Completed,
+        HideMode::Canceled,
+        HideMode::Finished,
+    ];
+
+    // What is hidden, empty when nothing is.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            HideMode::None => "",
+            HideMode::Completed => "completed",
+            HideMode::Canceled => "canceled",
+            HideMode::Finished => "completed and canceled",
+        }
+    }
+
+    pub fn next(&self) -> HideMode {
+        let index = HideMode::ALL.iter().position(|m| m == self).unwrap();
+        HideMode::ALL[(index + 1) % HideMode::ALL.len()]
+    }
+
+    pub fn hides(&self, state: TaskState) -> bool {
+        matches!(
+            (self, state),
+            (
+                HideMode::Completed | HideMode::Finished,
+                TaskState::Completed
+            ) | (HideMode::Canceled | HideMode::Finished, TaskState::Canceled)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskView {
+    pub sort: SortKey,
+    pub descending: bool,
+    // Sections per state, in board column order, each one sorted by `sort`.
+    pub grouped: bool,
+    // `None` follows the `hide_tasks` setting of the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hide: Option<HideMode>,
+}
+
+impl TaskView {
+    pub fn is_default(&self) -> bool {
+        *self == TaskView::default()
+    }
+
+    // Short summary for the task list border, empty for the default view.
+    pub fn get_description(&self) -> String {
+        let mut parts = vec![];
+        if self.sort != SortKey::Manual || self.descending {
+            let order = if self.descending { " desc" } else { "" };
+            parts.push(format!("sorted by {}{order}", self.sort.get_name()));
+        }
+        if self.grouped {
+            parts.push(String::from("grouped"));
+        }
+        parts.join(", ")
     }
 }
 
 // Project stuff
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Project {
     pub id: u32,
     pub name: String,
     pub is_current: bool,
     pub tasks: Vec<Task>,
+    #[serde(default, skip_serializing_if = "TaskView::is_default")]
+    pub view: TaskView,
 }
 
 impl Project {
@@ -187,9 +679,30 @@ impl Project {
             name,
             is_current: false,
             tasks: vec![],
+            view: TaskView::default(),
         }
     }
 
+    // Tasks in display order, following `view`.
+    pub fn get_sorted_tasks(&self) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.tasks.iter().collect();
+        tasks.sort_by(|a, b| {
+            let order = self.view.sort.compare(a, b);
+            if self.view.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        if self.view.descending && self.view.sort == SortKey::Manual {
+            tasks.reverse();
+        }
+        if self.view.grouped {
+            tasks.sort_by_key(|t| get_state_index(t.state));
+        }
+        tasks
+    }
+
     pub fn toggle_current(&mut self) {
         self.is_current = !self.is_current;
     }
@@ -201,6 +714,18 @@ impl Project {
         self.tasks.retain(|task| task.id != id);
     }
 
+    // Removes the tasks with these ids, to insert them in another project.
+    pub fn take_tasks(&mut self, ids: &[u32]) -> Vec<Task> {
+        let (taken, kept) = self.tasks.drain(..).partition(|t| ids.contains(&t.id));
+        self.tasks = kept;
+        taken
+    }
+
+    pub fn insert_task(&mut self, mut task: Task) {
+        task.project_id = self.id;
+        self.tasks.push(task);
+    }
+
     pub fn toggle_task_state(&mut self, id: u32) {
         for task in &mut self.tasks {
             if task.id == id {
@@ -217,6 +742,22 @@ impl Project {
         }
     }
 
+    pub fn complete_task(&mut self, id: u32) {
+        for task in &mut self.tasks {
+            if task.id == id {
+                task.complete();
+            }
+        }
+    }
+
+    pub fn set_task_state(&mut self, id: u32, state: TaskState) {
+        for task in &mut self.tasks {
+            if task.id == id {
+                task.set_state(state);
+            }
+        }
+    }
+
     pub fn rename_task(&mut self, id: u32, new_title: String) {
         for task in &mut self.tasks {
             if task.id == id {
@@ -252,6 +793,190 @@ impl Eq for Project {}
 
 // Tests
 
+#[cfg(test)]
+mod cursor_manager_test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn type_text(cursor_manager: &mut CursorManager, text: &str) {
+        for c in text.chars() {
+            cursor_manager.insert(c);
+        }
+    }
+
+    #[test]
+    fn graphemes() {
+        let mut cursor_manager = CursorManager::new();
+
+        // "e" followed by a combining acute accent is a single grapheme.
+        type_text(&mut cursor_manager, "cafe\u{301}👍🏽");
+        cursor_manager.delete();
+        assert_eq!(cursor_manager.string, "cafe\u{301}");
+        cursor_manager.move_cursor(CursorDirection::Left);
+        assert_eq!(cursor_manager.cursor, 3);
+        cursor_manager.insert('f');
+        assert_eq!(cursor_manager.string, "caffe\u{301}");
+        cursor_manager.move_cursor(CursorDirection::Right);
+        cursor_manager.move_cursor(CursorDirection::Right);
+        assert_eq!(cursor_manager.cursor, cursor_manager.string.len());
+    }
+
+    #[test]
+    fn display_position() {
+        let mut cursor_manager = CursorManager::new();
+
+        cursor_manager.set_string(String::from("中文 ok"));
+        assert_eq!(cursor_manager.get_display_position(), 7);
+        cursor_manager.move_cursor(CursorDirection::Left);
+        cursor_manager.move_cursor(CursorDirection::Left);
+        cursor_manager.move_cursor(CursorDirection::Left);
+        assert_eq!(cursor_manager.get_display_position(), 4);
+    }
+
+    #[test]
+    fn readline_editing() {
+        let mut cursor_manager = CursorManager::new();
+
+        cursor_manager.set_string(String::from("buy  some milk!"));
+        cursor_manager.move_cursor(CursorDirection::WordLeft);
+        assert_eq!(cursor_manager.cursor, 10);
+        cursor_manager.move_cursor(CursorDirection::WordLeft);
+        cursor_manager.move_cursor(CursorDirection::WordRight);
+        assert_eq!(cursor_manager.cursor, 9);
+        cursor_manager.kill(CursorDirection::WordLeft);
+        assert_eq!(cursor_manager.string, "buy   milk!");
+        cursor_manager.kill(CursorDirection::WordRight);
+        assert_eq!(cursor_manager.string, "buy  !");
+        cursor_manager.yank();
+        assert_eq!(cursor_manager.string, "buy   milk!");
+
+        cursor_manager.move_cursor(CursorDirection::Start);
+        cursor_manager.delete_next();
+        cursor_manager.kill(CursorDirection::End);
+        assert_eq!(cursor_manager.string, "");
+        assert_eq!(cursor_manager.yank_buffer, "uy   milk!");
+
+        cursor_manager.insert_str("two\r\nlines\u{7}");
+        assert_eq!(cursor_manager.string, "two  lines");
+        cursor_manager.move_cursor(CursorDirection::WordLeft);
+        cursor_manager.kill(CursorDirection::Start);
+        assert_eq!(cursor_manager.string, "lines");
+        assert_eq!(cursor_manager.cursor, 0);
+    }
+
+    #[test]
+    fn completion() {
+        let candidates: Vec<String> = ["Other Project", "milk", "Milkshake", "#shop"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut cursor_manager = CursorManager::new();
+
+        type_text(&mut cursor_manager, "buy mil");
+        assert!(cursor_manager.complete(&candidates));
+        assert_eq!(cursor_manager.string, "buy milk");
+        cursor_manager.complete(&candidates);
+        assert_eq!(cursor_manager.string, "buy Milkshake");
+        cursor_manager.complete(&candidates);
+        assert_eq!(cursor_manager.string, "buy mil");
+
+        type_text(&mut cursor_manager, "k for other p");
+        cursor_manager.complete(&candidates);
+        assert_eq!(cursor_manager.string, "buy milk for Other Project");
+        type_text(&mut cursor_manager, " #");
+        cursor_manager.complete(&candidates);
+        assert_eq!(cursor_manager.string, "buy milk for Other Project #shop");
+        type_text(&mut cursor_manager, " ");
+        assert!(!cursor_manager.complete(&candidates));
+    }
+
+    #[derive(Debug, Clone)]
+    enum Edit {
+        Insert(char),
+        Delete,
+        DeleteNext,
+        Move(CursorDirection),
+        Kill(CursorDirection),
+        Yank,
+        Paste(String),
+        Set(String),
+    }
+
+    fn direction() -> impl Strategy<Value = CursorDirection> {
+        prop::sample::select(vec![
+            CursorDirection::Left,
+            CursorDirection::Right,
+            CursorDirection::WordLeft,
+            CursorDirection::WordRight,
+            CursorDirection::Start,
+            CursorDirection::End,
+        ])
+    }
+
+    fn tricky_char() -> impl Strategy<Value = char> {
+        prop_oneof![
+            any::<char>(),
+            prop::sample::select(vec![
+                'a',
+                'é',
+                '\u{301}',
+                '\u{200D}',
+                '\u{FE0F}',
+                '👍',
+                '\u{1F3FD}',
+                '🇫',
+                '🇷',
+                '中',
+                '\r',
+                '\n',
+                '\t',
+            ]),
+        ]
+    }
+
+    fn edit() -> impl Strategy<Value = Edit> {
+        prop_oneof![
+            4 => tricky_char().prop_map(Edit::Insert),
+            2 => Just(Edit::Delete),
+            1 => Just(Edit::DeleteNext),
+            4 => direction().prop_map(Edit::Move),
+            1 => direction().prop_map(Edit::Kill),
+            1 => Just(Edit::Yank),
+            1 => prop::collection::vec(tricky_char(), 0..8)
+                .prop_map(|chars| Edit::Paste(chars.into_iter().collect())),
+            1 => prop::collection::vec(tricky_char(), 0..8)
+                .prop_map(|chars| Edit::Set(chars.into_iter().collect())),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn random_edits_never_panic(edits in prop::collection::vec(edit(), 0..64)) {
+            let mut cursor_manager = CursorManager::new();
+            for edit in edits {
+                match edit {
+                    Edit::Insert(c) => cursor_manager.insert(c),
+                    Edit::Delete => cursor_manager.delete(),
+                    Edit::DeleteNext => cursor_manager.delete_next(),
+                    Edit::Move(dir) => cursor_manager.move_cursor(dir),
+                    Edit::Kill(dir) => cursor_manager.kill(dir),
+                    Edit::Yank => cursor_manager.yank(),
+                    Edit::Paste(s) => cursor_manager.insert_str(&s),
+                    Edit::Set(s) => cursor_manager.set_string(s),
+                }
+                let string = &cursor_manager.string;
+                let cursor = cursor_manager.cursor;
+                prop_assert!(cursor <= string.len());
+                prop_assert!(
+                    cursor == string.len()
+                        || string.grapheme_indices(true).any(|(i, _)| i == cursor)
+                );
+                prop_assert!(cursor_manager.get_display_position() as usize <= string.width());
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod file_manager_test {
     use super::*;
@@ -287,6 +1012,54 @@ mod project_test {
         assert_eq!(project.tasks.len(), 1);
         assert_eq!(project.tasks[0].title, String::from("Salut"))
     }
+
+    #[test]
+    fn get_sorted_tasks() {
+        let mut project = Project::new(1, String::from("Projet 1"));
+        project.add_task(3, String::from("banane"));
+        project.add_task(1, String::from("Cerise"));
+        project.add_task(2, String::from("abricot"));
+        project.toggle_task_state(1);
+        let titles = |project: &Project| -> Vec<String> {
+            project
+                .get_sorted_tasks()
+                .iter()
+                .map(|t| t.title.clone())
+                .collect()
+        };
+
+        assert_eq!(titles(&project), ["banane", "Cerise", "abricot"]);
+        project.view.descending = true;
+        assert_eq!(titles(&project), ["abricot", "Cerise", "banane"]);
+        project.view.sort = SortKey::Title;
+        assert_eq!(titles(&project), ["Cerise", "banane", "abricot"]);
+        project.view.grouped = true;
+        assert_eq!(titles(&project), ["banane", "abricot", "Cerise"]);
+        project.view = TaskView {
+            sort: SortKey::Id,
+            ..TaskView::default()
+        };
+        assert_eq!(titles(&project), ["Cerise", "abricot", "banane"]);
+        assert_eq!(project.view.get_description(), "sorted by id");
+
+        // Creation time first, the id only breaks ties.
+        project.tasks[0].created_at = 20;
+        project.tasks[1].created_at = 30;
+        project.tasks[2].created_at = 20;
+        project.view.sort = SortKey::Created;
+        assert_eq!(titles(&project), ["abricot", "banane", "Cerise"]);
+        assert_eq!(project.view.get_description(), "sorted by creation date");
+    }
+
+    #[test]
+    fn hide_mode() {
+        assert!(!HideMode::None.hides(TaskState::Completed));
+        assert!(HideMode::Completed.hides(TaskState::Completed));
+        assert!(!HideMode::Completed.hides(TaskState::Canceled));
+        assert!(HideMode::Finished.hides(TaskState::Canceled));
+        assert!(!HideMode::Finished.hides(TaskState::Todo));
+        assert_eq!(HideMode::Finished.next(), HideMode::None);
+    }
 }
 
 #[cfg(test)]
@@ -300,8 +1073,10 @@ mod task_test {
         assert!(matches!(task.state, TaskState::Todo));
         task.toggle_state();
         assert!(matches!(task.state, TaskState::Completed));
+        assert!(task.completed_at.is_some());
         task.toggle_state();
         assert!(matches!(task.state, TaskState::Todo));
+        assert!(task.completed_at.is_none());
     }
 
     #[test]
@@ -314,6 +1089,48 @@ mod task_test {
         assert!(matches!(task.state, TaskState::Todo));
     }
 
+    #[test]
+    fn timestamps() {
+        let mut task = Task::new(1, 1, "Tache".to_string());
+        assert!(task.created_at > 0);
+        assert_eq!(task.modified_at, task.created_at);
+        task.modified_at = 0;
+        task.rename(String::from("Tache"));
+        assert_eq!(task.modified_at, 0);
+        task.rename(String::from("Autre tache"));
+        assert!(task.modified_at >= task.created_at);
+        task.modified_at = 0;
+        task.cancel();
+        assert!(task.modified_at > 0);
+        assert_eq!(task.completed_at, Some(task.modified_at));
+    }
+
+    #[test]
+    fn backfill() {
+        let json = r#"{"id": 1, "project_id": 1, "state": "Completed", "title": "Old"}"#;
+        let mut task: Task = serde_json::from_str(json).unwrap();
+        task.backfill(1000);
+        assert_eq!(task.created_at, 1000);
+        assert_eq!(task.modified_at, 1000);
+        assert_eq!(task.completed_at, Some(1000));
+
+        task.modified_at = 2000;
+        task.backfill(3000);
+        assert_eq!(task.created_at, 1000);
+        assert_eq!(task.modified_at, 2000);
+    }
+
+    #[test]
+    fn complete() {
+        let mut task = Task::new(1, 1, "Tache".to_string());
+
+        task.cancel();
+        task.complete();
+        assert!(matches!(task.state, TaskState::Completed));
+        task.complete();
+        assert!(matches!(task.state, TaskState::Completed));
+    }
+
     #[test]
     fn rename() {
         let mut task = Task::new(1, 1, "Salut".to_string());
@@ -321,4 +1138,11 @@ mod task_test {
         task.rename("Bonjour".to_string());
         assert_eq!(task.title, "Bonjour".to_string())
     }
+
+    #[test]
+    fn tags() {
+        let task = Task::new(1, 1, "Relire #review la doc # #docs".to_string());
+
+        assert_eq!(task.tags(), vec!["review", "docs"]);
+    }
 }
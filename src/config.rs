@@ -0,0 +1,153 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    comps::HideMode,
+    keymap::KeyOverrides,
+    theme::{Theme, ThemeConfig},
+};
+
+// Config stuff
+//
+// Read from `$XDG_CONFIG_HOME/tuido/config.toml`, falling back to
+// `~/.config/tuido/config.toml`. A missing file means default settings.
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub sidebar: bool,
+    pub vi_mode: bool,
+    pub hide_tasks: HideMode,
+    pub archive_after_days: Option<u64>,
+    pub show_dates: bool,
+    pub theme: Option<String>,
+    pub themes: HashMap<String, ThemeConfig>,
+    pub keys: KeyOverrides,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            sidebar: true,
+            vi_mode: false,
+            hide_tasks: HideMode::None,
+            archive_after_days: None,
+            show_dates: false,
+            theme: None,
+            themes: HashMap::new(),
+            keys: KeyOverrides::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        match Config::get_path() {
+            Some(path) if path.exists() => Config::read(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        Config::parse(&content).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn get_theme(&self) -> Result<Theme, Box<dyn Error>> {
+        Theme::build(self.theme.as_deref(), &self.themes)
+    }
+
+    pub fn get_path() -> Option<PathBuf> {
+        let mut path = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => {
+                let mut home = PathBuf::from(env::var_os("HOME")?);
+                home.push(".config");
+                home
+            }
+        };
+        path.push("tuido");
+        path.push("config.toml");
+        Some(path)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod config_test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            "[keys.main]\n\
+             delete_task = [\"dd\"]\n\
+             first_task = [\"gg\", \"Home\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.keys["main"]["first_task"], vec!["gg", "Home"]);
+    }
+
+    #[test]
+    fn parse_theme() {
+        let config = Config::parse(
+            "theme = \"mine\"\n\
+             [themes.mine]\n\
+             base = \"high-contrast\"\n\
+             glyph_set = \"ascii\"\n\
+             styles = { todo = \"white on black\" }\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.get_theme().unwrap().glyphs.todo, "[ ]");
+        assert!(Config::parse("theme = \"neon\"")
+            .unwrap()
+            .get_theme()
+            .is_err());
+        assert!(Config::default().get_theme().is_ok());
+    }
+
+    #[test]
+    fn parse_empty() {
+        let config = Config::parse("").unwrap();
+
+        assert!(config.keys.is_empty());
+        assert!(config.sidebar);
+        assert!(!config.vi_mode);
+        assert!(!Config::parse("sidebar = false").unwrap().sidebar);
+        assert!(Config::parse("vi_mode = true").unwrap().vi_mode);
+        assert_eq!(config.hide_tasks, HideMode::None);
+        assert_eq!(
+            Config::parse("hide_tasks = \"finished\"")
+                .unwrap()
+                .hide_tasks,
+            HideMode::Finished
+        );
+        assert!(Config::parse("hide_tasks = \"todo\"").is_err());
+        assert_eq!(config.archive_after_days, None);
+        assert!(!config.show_dates);
+        assert_eq!(
+            Config::parse("archive_after_days = 14")
+                .unwrap()
+                .archive_after_days,
+            Some(14)
+        );
+    }
+
+    #[test]
+    fn parse_unknown_section() {
+        assert!(Config::parse("[colors]\nfoo = 1\n").is_err());
+    }
+}
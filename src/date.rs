@@ -0,0 +1,47 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Date stuff
+//
+// Timestamps are seconds since the Unix epoch, shown as UTC calendar dates.
+
+pub const DAY: u64 = 24 * 60 * 60;
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+// `YYYY-MM-DD`, from the days-to-civil algorithm of Howard Hinnant.
+pub fn format_date(timestamp: u64) -> String {
+    let days = (timestamp / DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Tests
+
+#[cfg(test)]
+mod date_test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+    }
+}
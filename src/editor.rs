@@ -0,0 +1,215 @@
+use std::{env, error::Error, fs, io::Write, path::Path, process};
+
+use crate::{
+    comps::{Task, TaskState},
+    date,
+};
+
+// External editor stuff
+//
+// A task is written as a small text document: `Key: value` header lines, a
+// blank line, then free form notes. `run_app` suspends the terminal while
+// the editor runs and hands the result back through `Action::ApplyEdit`.
+
+const HELP: &str = "\
+# Edit the task, save and quit. Comments before the notes are ignored.
+# State is Todo, Completed or Canceled. Notes go after the first blank line.
+";
+const ERROR_PREFIX: &str = "# Error: ";
+
+pub struct TaskEdit {
+    pub title: String,
+    pub state: TaskState,
+    pub notes: String,
+}
+
+pub fn to_document(task: &Task) -> String {
+    let mut dates = format!(
+        "# Created {}, modified {}",
+        date::format_date(task.created_at),
+        date::format_date(task.modified_at)
+    );
+    if let Some(completed_at) = task.completed_at {
+        dates.push_str(&format!(", finished {}", date::format_date(completed_at)));
+    }
+    let mut document = format!(
+        "{HELP}{dates}\nTitle: {}\nState: {}\n\n",
+        task.title,
+        task.state.get_name()
+    );
+    document.push_str(&task.notes);
+    if !task.notes.is_empty() {
+        document.push('\n');
+    }
+    document
+}
+
+// Puts the error on top of a document that failed to parse, replacing the
+// previous one.
+pub fn add_error(document: &str, error: &str) -> String {
+    let mut with_error = format!("{ERROR_PREFIX}{error}\n");
+    for line in document.lines().filter(|l| !l.starts_with(ERROR_PREFIX)) {
+        with_error.push_str(line);
+        with_error.push('\n');
+    }
+    with_error
+}
+
+pub fn from_document(document: &str) -> Result<TaskEdit, Box<dyn Error>> {
+    let mut title = None;
+    let mut state = None;
+    let mut lines = document.lines().enumerate();
+
+    for (i, line) in lines.by_ref() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(format!("line {}: expected 'Title: ...' or 'State: ...'", i + 1).into());
+        };
+        let value = value.trim();
+        let field = match key.trim().to_lowercase().as_str() {
+            "title" => &mut title,
+            "state" => &mut state,
+            key => return Err(format!("line {}: unknown field '{key}'", i + 1).into()),
+        };
+        if field.is_some() {
+            return Err(format!("line {}: '{}' is set twice", i + 1, key.trim()).into());
+        }
+        *field = Some((i, value));
+    }
+
+    let title = match title {
+        Some((i, "")) => return Err(format!("line {}: the title is empty", i + 1).into()),
+        Some((_, title)) => title.to_string(),
+        None => return Err("the title is missing".into()),
+    };
+    let state = match state {
+        Some((i, name)) => *TaskState::ALL
+            .iter()
+            .find(|s| s.get_name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "line {}: unknown state '{name}' (expected Todo, Completed or Canceled)",
+                    i + 1
+                )
+            })?,
+        None => TaskState::Todo,
+    };
+    let notes: Vec<&str> = lines.map(|(_, line)| line).collect();
+    let notes = notes.join("\n").trim_matches('\n').trim_end().to_string();
+
+    Ok(TaskEdit {
+        title,
+        state,
+        notes,
+    })
+}
+
+// Runs `$VISUAL` or `$EDITOR` (falling back to `vi`) on a temporary file
+// holding `document` and returns the saved text.
+pub fn open(document: &str) -> Result<String, Box<dyn Error>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("$EDITOR is empty")?;
+
+    let mut path = env::temp_dir();
+    path.push(format!("tuido-task-{}.txt", process::id()));
+    write_private(&path, document)?;
+    let status = process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status();
+    let text = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.map_err(|e| format!("cannot run '{editor}': {e}"))?;
+    if !status.success() {
+        return Err(format!("'{editor}' exited with {status}").into());
+    }
+    Ok(text?)
+}
+
+// The temp dir is shared: refuse a file someone else already placed there
+// (or a symlink to one) and keep the task unreadable by other users.
+fn write_private(path: &Path, document: &str) -> Result<(), Box<dyn Error>> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("cannot create {}: {e}", path.display()))?;
+    file.write_all(document.as_bytes())?;
+    Ok(())
+}
+
+// Tests
+
+#[cfg(test)]
+mod editor_test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut task = Task::new(1, 1, String::from("Buy milk"));
+        task.state = TaskState::Completed;
+        task.notes = String::from("Two bottles\n\nFrom the farm");
+
+        let edit = from_document(&to_document(&task)).unwrap();
+        assert_eq!(edit.title, "Buy milk");
+        assert_eq!(edit.state, TaskState::Completed);
+        assert_eq!(edit.notes, task.notes);
+
+        let edit = from_document("title:  Call Bob \n\n\n# Not a comment\n\n").unwrap();
+        assert_eq!(edit.title, "Call Bob");
+        assert_eq!(edit.state, TaskState::Todo);
+        assert_eq!(edit.notes, "# Not a comment");
+    }
+
+    #[test]
+    fn errors() {
+        let error = |document: &str| from_document(document).err().unwrap().to_string();
+
+        assert_eq!(error("State: Todo\n"), "the title is missing");
+        assert_eq!(error("# Help\nTitle:\n"), "line 2: the title is empty");
+        assert_eq!(
+            error("Title: a\nPriority: 1\n"),
+            "line 2: unknown field 'priority'"
+        );
+        assert!(error("Title: a\nState: Doing\n").starts_with("line 2: unknown state 'Doing'"));
+        assert_eq!(
+            error("Title: a\nTitle: b\n"),
+            "line 2: 'Title' is set twice"
+        );
+
+        let document = add_error(&add_error("Title:\n", "first"), "second");
+        assert_eq!(document, "# Error: second\nTitle:\n");
+    }
+
+    #[test]
+    fn private_file() {
+        let path = env::temp_dir().join(format!("tuido-{}-private.txt", process::id()));
+        let _ = fs::remove_file(&path);
+        write_private(&path, "Title: Task\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Title: Task\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(write_private(&path, "Other").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Title: Task\n");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,102 @@
+// History stuff
+//
+// Titles entered when adding tasks, oldest first. They are saved next to the
+// data file and browsed with Up/Down in the add task prompt, like a shell
+// history: the text being typed is kept and comes back after the newest
+// entry.
+
+const MAX_ENTRIES: usize = 200;
+
+pub struct History {
+    pub entries: Vec<String>,
+    // Entry shown in the prompt, `None` while editing new text.
+    position: Option<usize>,
+    draft: String,
+}
+
+impl History {
+    pub fn new(entries: Vec<String>) -> Self {
+        History {
+            entries,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    // A title entered again moves to the end instead of being duplicated.
+    pub fn push(&mut self, entry: &str) {
+        let entry = entry.trim();
+        if !entry.is_empty() {
+            self.entries.retain(|e| e != entry);
+            self.entries.push(entry.to_string());
+            let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+            self.entries.drain(..excess);
+        }
+        self.reset();
+    }
+
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => return None,
+            Some(position) => position - 1,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(&self.entries[position])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod history_test {
+    use super::*;
+
+    #[test]
+    fn navigation() {
+        let mut history = History::new(vec![String::from("one"), String::from("two")]);
+
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous("dra"), Some("two"));
+        assert_eq!(history.previous("two"), Some("one"));
+        assert_eq!(history.previous("one"), None);
+        assert_eq!(history.next(), Some("two"));
+        assert_eq!(history.next(), Some("dra"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn push() {
+        let mut history = History::new(vec![]);
+
+        for i in 0..MAX_ENTRIES {
+            history.push(&format!("task {i}"));
+        }
+        history.push(" task 0 ");
+        history.push("   ");
+        history.push("last");
+
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries[0], "task 2");
+        assert_eq!(history.entries[MAX_ENTRIES - 2..], ["task 0", "last"]);
+    }
+}
@@ -0,0 +1,757 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::HashMap, error::Error};
+
+use crate::{action::Action, app::ScreenMode};
+
+// Keymap stuff
+//
+// Single source of truth for the key bindings: `run_app` dispatches the
+// actions found by `Keymap::get_action` and the help popup is built from
+// `Keymap::get_help`. The defaults below can be overridden per context from
+// the config file.
+
+// Bindings are grouped by context: every text prompt shares the same keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Main,
+    Board,
+    Sidebar,
+    Prompt,
+    Palette,
+    Confirm,
+    Archive,
+}
+
+impl KeyContext {
+    pub const ALL: &'static [KeyContext] = &[
+        KeyContext::Main,
+        KeyContext::Board,
+        KeyContext::Sidebar,
+        KeyContext::Prompt,
+        KeyContext::Palette,
+        KeyContext::Confirm,
+        KeyContext::Archive,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            KeyContext::Main => "main",
+            KeyContext::Board => "board",
+            KeyContext::Sidebar => "sidebar",
+            KeyContext::Prompt => "prompt",
+            KeyContext::Palette => "palette",
+            KeyContext::Confirm => "confirm",
+            KeyContext::Archive => "archive",
+        }
+    }
+
+    // Contexts with a text input.
+    pub fn is_input(&self) -> bool {
+        matches!(self, KeyContext::Prompt | KeyContext::Palette)
+    }
+
+    pub fn from_mode(mode: &ScreenMode) -> Self {
+        match mode {
+            ScreenMode::Main => KeyContext::Main,
+            ScreenMode::Board => KeyContext::Board,
+            ScreenMode::Sidebar => KeyContext::Sidebar,
+            ScreenMode::AddingTask
+            | ScreenMode::RenamingTask
+            | ScreenMode::RenamingProject
+            | ScreenMode::MovingTasks
+            | ScreenMode::TaggingTasks
+            | ScreenMode::Filtering
+            | ScreenMode::Searching => KeyContext::Prompt,
+            ScreenMode::Palette => KeyContext::Palette,
+            ScreenMode::DeletingTask | ScreenMode::ConfirmingBatch => KeyContext::Confirm,
+            ScreenMode::Archive => KeyContext::Archive,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyPress {
+    pub const fn new(code: KeyCode) -> Self {
+        KeyPress {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    // Shift is already part of the character for `Char` keys, so it is
+    // ignored there to match both `G` and `Shift-G` events.
+    pub fn from_event(key: &KeyEvent) -> Self {
+        let mut modifiers =
+            key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(key.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        KeyPress {
+            code: key.code,
+            modifiers,
+        }
+    }
+
+    pub fn parse(key: &str) -> Result<Self, Box<dyn Error>> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = key;
+        loop {
+            let lower = rest.to_lowercase();
+            let prefix = ["ctrl-", "c-", "alt-", "a-", "m-", "shift-", "s-"]
+                .into_iter()
+                .find(|p| lower.starts_with(p) && rest.len() > p.len());
+            let Some(prefix) = prefix else { break };
+            modifiers |= match prefix {
+                "ctrl-" | "c-" => KeyModifiers::CONTROL,
+                "alt-" | "a-" | "m-" => KeyModifiers::ALT,
+                _ => KeyModifiers::SHIFT,
+            };
+            rest = &rest[prefix.len()..];
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "delete" | "del" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            name if name.len() > 1 && name.starts_with('f') && name[1..].parse::<u8>().is_ok() => {
+                KeyCode::F(name[1..].parse()?)
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key '{key}'").into()),
+                }
+            }
+        };
+        // Terminals send `Shift-g` as `G`, and `from_event` drops the flag.
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(u), None) => KeyCode::Char(u),
+                    _ => code,
+                }
+            }
+            _ => code,
+        };
+        Ok(KeyPress::from_event(&KeyEvent::new(code, modifiers)))
+    }
+
+    pub fn get_name(&self) -> String {
+        let mut name = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name.push_str("Ctrl-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name.push_str("Alt-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            name.push_str("Shift-");
+        }
+        name.push_str(&get_key_name(&self.code));
+        name
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPattern {
+    Keys(Vec<KeyPress>),
+    AnyChar,
+}
+
+impl KeyPattern {
+    // A sequence is written as space separated keys (`Ctrl-x Ctrl-s`). A
+    // short word that is not a key name is read as one key per character, so
+    // `gg` and `dd` are two key sequences. Longer words must be key names,
+    // so a typo like `pgup` is an error and not `p g u p`.
+    pub fn parse(pattern: &str) -> Result<Self, Box<dyn Error>> {
+        const MAX_CHARS: usize = 3;
+        let mut keys = vec![];
+        for word in pattern.split_whitespace() {
+            match KeyPress::parse(word) {
+                Ok(key) => keys.push(key),
+                Err(e) if word.contains('-') || word.chars().count() > MAX_CHARS => return Err(e),
+                Err(_) => keys.extend(word.chars().map(|c| KeyPress::new(KeyCode::Char(c)))),
+            }
+        }
+        if keys.is_empty() {
+            return Err("empty key binding".into());
+        }
+        Ok(KeyPattern::Keys(keys))
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            KeyPattern::Keys(keys) => {
+                let plain_chars = keys.iter().all(|k| {
+                    k.modifiers.is_empty() && matches!(k.code, KeyCode::Char(c) if c != ' ')
+                });
+                let names: Vec<String> = keys.iter().map(|k| k.get_name()).collect();
+                names.join(if plain_chars { "" } else { " " })
+            }
+            KeyPattern::AnyChar => String::from("Any character"),
+        }
+    }
+}
+
+pub enum KeyLookup {
+    Action(Action),
+    Pending,
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub key: KeyPattern,
+    pub action: Action,
+}
+
+pub type KeyOverrides = HashMap<String, HashMap<String, Vec<String>>>;
+
+pub struct Keymap {
+    bindings: HashMap<KeyContext, Vec<Binding>>,
+}
+
+fn key(code: KeyCode) -> KeyPattern {
+    KeyPattern::Keys(vec![KeyPress::new(code)])
+}
+
+fn ctrl(c: char) -> KeyPattern {
+    KeyPattern::Keys(vec![KeyPress {
+        code: KeyCode::Char(c),
+        modifiers: KeyModifiers::CONTROL,
+    }])
+}
+
+fn alt(code: KeyCode) -> KeyPattern {
+    KeyPattern::Keys(vec![KeyPress {
+        code,
+        modifiers: KeyModifiers::ALT,
+    }])
+}
+
+fn ctrl_key(code: KeyCode) -> KeyPattern {
+    KeyPattern::Keys(vec![KeyPress {
+        code,
+        modifiers: KeyModifiers::CONTROL,
+    }])
+}
+
+fn keys(codes: &[KeyCode]) -> KeyPattern {
+    KeyPattern::Keys(codes.iter().map(|c| KeyPress::new(*c)).collect())
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let main = vec![
+            (key(KeyCode::Char('q')), Action::Quit),
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Left), Action::PreviousProject),
+            (key(KeyCode::Right), Action::NextProject),
+            (key(KeyCode::Up), Action::PreviousTask),
+            (key(KeyCode::Char('k')), Action::PreviousTask),
+            (key(KeyCode::Down), Action::NextTask),
+            (key(KeyCode::Char('j')), Action::NextTask),
+            (
+                keys(&[KeyCode::Char('g'), KeyCode::Char('g')]),
+                Action::FirstTask,
+            ),
+            (key(KeyCode::Home), Action::FirstTask),
+            (key(KeyCode::Char('G')), Action::LastTask),
+            (key(KeyCode::End), Action::LastTask),
+            (key(KeyCode::PageUp), Action::PageUp),
+            (key(KeyCode::PageDown), Action::PageDown),
+            (key(KeyCode::Enter), Action::ToggleTask),
+            (key(KeyCode::Char('c')), Action::CancelTask),
+            (key(KeyCode::Char('a')), Action::AddTask),
+            (key(KeyCode::Char('r')), Action::RenameTask),
+            (key(KeyCode::Char('e')), Action::EditTask),
+            (key(KeyCode::Char('d')), Action::DeleteTask),
+            (key(KeyCode::Char(' ')), Action::ToggleMark),
+            (key(KeyCode::Char('V')), Action::MarkRange),
+            (key(KeyCode::Char('*')), Action::MarkAll),
+            (key(KeyCode::Char('S')), Action::MarkSameState),
+            (key(KeyCode::Char('m')), Action::MoveTasks),
+            (key(KeyCode::Char('t')), Action::TagTasks),
+            (key(KeyCode::Char('u')), Action::Undo),
+            (key(KeyCode::Char('P')), Action::NewProject),
+            (key(KeyCode::Char('p')), Action::RenameProject),
+            (key(KeyCode::Char('s')), Action::ToggleSidebar),
+            (key(KeyCode::Tab), Action::SwitchFocus),
+            (key(KeyCode::Char('b')), Action::ToggleBoard),
+            (key(KeyCode::Char('o')), Action::CycleSort),
+            (key(KeyCode::Char('O')), Action::ReverseSort),
+            (key(KeyCode::Char('=')), Action::ToggleGrouping),
+            (key(KeyCode::Char('v')), Action::CycleHidden),
+            (key(KeyCode::Char('A')), Action::ArchiveDone),
+            (key(KeyCode::Char('Z')), Action::ShowArchive),
+            (key(KeyCode::Char('D')), Action::ToggleDates),
+            (key(KeyCode::Esc), Action::ClearMarks),
+            (key(KeyCode::Char('f')), Action::Filter),
+            (key(KeyCode::Char('/')), Action::Search),
+            (key(KeyCode::Char('n')), Action::NextMatch),
+            (key(KeyCode::Char('N')), Action::PreviousMatch),
+            (key(KeyCode::Char(':')), Action::Palette),
+            (ctrl('p'), Action::Palette),
+        ];
+        let board = vec![
+            (key(KeyCode::Char('q')), Action::Quit),
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Up), Action::PreviousTask),
+            (key(KeyCode::Char('k')), Action::PreviousTask),
+            (key(KeyCode::Down), Action::NextTask),
+            (key(KeyCode::Char('j')), Action::NextTask),
+            (key(KeyCode::Char('h')), Action::PreviousColumn),
+            (key(KeyCode::Char('l')), Action::NextColumn),
+            (key(KeyCode::Left), Action::MoveTaskLeft),
+            (key(KeyCode::Char('H')), Action::MoveTaskLeft),
+            (key(KeyCode::Right), Action::MoveTaskRight),
+            (key(KeyCode::Char('L')), Action::MoveTaskRight),
+            (key(KeyCode::Char('[')), Action::PreviousProject),
+            (key(KeyCode::Char(']')), Action::NextProject),
+            (key(KeyCode::Enter), Action::ToggleTask),
+            (key(KeyCode::Char('c')), Action::CancelTask),
+            (key(KeyCode::Char('a')), Action::AddTask),
+            (key(KeyCode::Char('r')), Action::RenameTask),
+            (key(KeyCode::Char('e')), Action::EditTask),
+            (key(KeyCode::Char('d')), Action::DeleteTask),
+            (key(KeyCode::Char(' ')), Action::ToggleMark),
+            (key(KeyCode::Char('V')), Action::MarkRange),
+            (key(KeyCode::Char('*')), Action::MarkAll),
+            (key(KeyCode::Char('S')), Action::MarkSameState),
+            (key(KeyCode::Char('m')), Action::MoveTasks),
+            (key(KeyCode::Char('t')), Action::TagTasks),
+            (key(KeyCode::Char('u')), Action::Undo),
+            (key(KeyCode::Char('f')), Action::Filter),
+            (key(KeyCode::Char('/')), Action::Search),
+            (key(KeyCode::Char('n')), Action::NextMatch),
+            (key(KeyCode::Char('N')), Action::PreviousMatch),
+            (key(KeyCode::Char('s')), Action::ToggleSidebar),
+            (key(KeyCode::Tab), Action::SwitchFocus),
+            (key(KeyCode::Char('b')), Action::ToggleBoard),
+            (key(KeyCode::Esc), Action::ToggleBoard),
+            (key(KeyCode::Char('o')), Action::CycleSort),
+            (key(KeyCode::Char('O')), Action::ReverseSort),
+            (key(KeyCode::Char('v')), Action::CycleHidden),
+            (key(KeyCode::Char('A')), Action::ArchiveDone),
+            (key(KeyCode::Char('Z')), Action::ShowArchive),
+            (key(KeyCode::Char(':')), Action::Palette),
+            (ctrl('p'), Action::Palette),
+        ];
+        let sidebar = vec![
+            (key(KeyCode::Char('q')), Action::Quit),
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Up), Action::PreviousProject),
+            (key(KeyCode::Char('k')), Action::PreviousProject),
+            (key(KeyCode::Down), Action::NextProject),
+            (key(KeyCode::Char('j')), Action::NextProject),
+            (key(KeyCode::Tab), Action::SwitchFocus),
+            (key(KeyCode::Enter), Action::SwitchFocus),
+            (key(KeyCode::Esc), Action::SwitchFocus),
+            (key(KeyCode::Char('P')), Action::NewProject),
+            (key(KeyCode::Char('p')), Action::RenameProject),
+            (key(KeyCode::Char('s')), Action::ToggleSidebar),
+            (key(KeyCode::Char(':')), Action::Palette),
+            (ctrl('p'), Action::Palette),
+        ];
+        // Readline style editing, shared by the prompts and the palette.
+        let editing = vec![
+            (key(KeyCode::Backspace), Action::DeleteChar),
+            (key(KeyCode::Delete), Action::DeleteNextChar),
+            (ctrl('w'), Action::DeleteWordLeft),
+            (alt(KeyCode::Backspace), Action::DeleteWordLeft),
+            (alt(KeyCode::Char('d')), Action::DeleteWordRight),
+            (ctrl('u'), Action::KillToStart),
+            (ctrl('k'), Action::KillToEnd),
+            (ctrl('y'), Action::Yank),
+            (key(KeyCode::Left), Action::CursorLeft),
+            (key(KeyCode::Right), Action::CursorRight),
+            (ctrl_key(KeyCode::Left), Action::WordLeft),
+            (alt(KeyCode::Char('b')), Action::WordLeft),
+            (ctrl_key(KeyCode::Right), Action::WordRight),
+            (alt(KeyCode::Char('f')), Action::WordRight),
+            (key(KeyCode::Home), Action::CursorStart),
+            (ctrl('a'), Action::CursorStart),
+            (key(KeyCode::End), Action::CursorEnd),
+            (ctrl('e'), Action::CursorEnd),
+            // The typed character replaces the placeholder on lookup.
+            (KeyPattern::AnyChar, Action::InsertChar(' ')),
+        ];
+        let mut prompt = vec![
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Enter), Action::Validate),
+            (key(KeyCode::Esc), Action::Abort),
+            (key(KeyCode::Up), Action::PreviousHistory),
+            (key(KeyCode::Down), Action::NextHistory),
+            (key(KeyCode::Tab), Action::Complete),
+        ];
+        prompt.extend(editing.clone());
+        let mut palette = vec![
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Enter), Action::Validate),
+            (key(KeyCode::Esc), Action::Abort),
+            (key(KeyCode::Up), Action::PreviousItem),
+            (ctrl('p'), Action::PreviousItem),
+            (key(KeyCode::Down), Action::NextItem),
+            (ctrl('n'), Action::NextItem),
+        ];
+        palette.extend(editing);
+        let confirm = vec![
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Char('y')), Action::Confirm),
+            (key(KeyCode::Char('n')), Action::Abort),
+            (key(KeyCode::Esc), Action::Abort),
+        ];
+        let archive = vec![
+            (key(KeyCode::Char('q')), Action::Quit),
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Up), Action::PreviousItem),
+            (key(KeyCode::Char('k')), Action::PreviousItem),
+            (key(KeyCode::Down), Action::NextItem),
+            (key(KeyCode::Char('j')), Action::NextItem),
+            (key(KeyCode::Left), Action::PreviousProject),
+            (key(KeyCode::Right), Action::NextProject),
+            (key(KeyCode::Enter), Action::RestoreTask),
+            (key(KeyCode::Char('r')), Action::RestoreTask),
+            (key(KeyCode::Char('Z')), Action::ShowArchive),
+            (key(KeyCode::Esc), Action::Abort),
+        ];
+
+        let to_bindings = |table: Vec<(KeyPattern, Action)>| {
+            table
+                .into_iter()
+                .map(|(key, action)| Binding { key, action })
+                .collect()
+        };
+        Keymap {
+            bindings: HashMap::from([
+                (KeyContext::Main, to_bindings(main)),
+                (KeyContext::Board, to_bindings(board)),
+                (KeyContext::Sidebar, to_bindings(sidebar)),
+                (KeyContext::Prompt, to_bindings(prompt)),
+                (KeyContext::Palette, to_bindings(palette)),
+                (KeyContext::Confirm, to_bindings(confirm)),
+                (KeyContext::Archive, to_bindings(archive)),
+            ]),
+        }
+    }
+}
+
+impl Keymap {
+    // `overrides` maps a context name to action names and their keys. Each
+    // listed action has its default keys replaced, an empty list unbinds it.
+    pub fn build(overrides: &KeyOverrides) -> Result<Self, Box<dyn Error>> {
+        let mut keymap = Keymap::default();
+        for (context_name, actions) in overrides {
+            let context = KeyContext::ALL
+                .iter()
+                .find(|c| c.get_name() == context_name)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = KeyContext::ALL.iter().map(|c| c.get_name()).collect();
+                    format!(
+                        "unknown key context '{context_name}' (expected one of {})",
+                        names.join(", ")
+                    )
+                })?;
+            let bindings = keymap.bindings.get_mut(context).unwrap();
+            for (action_name, keys) in actions {
+                // Typing in a prompt used to be the `insert_char` binding, it
+                // is now built in: older configs listing it still load.
+                if action_name == "insert_char" {
+                    continue;
+                }
+                let action = Action::from_name(action_name).ok_or_else(|| {
+                    format!("unknown action '{action_name}' in [keys.{context_name}]")
+                })?;
+                bindings.retain(|b| b.action != action);
+                let action = &action;
+                for key in keys {
+                    let key = KeyPattern::parse(key)
+                        .map_err(|e| format!("{e} for '{action_name}' in [keys.{context_name}]"))?;
+                    bindings.push(Binding {
+                        key,
+                        action: action.clone(),
+                    });
+                }
+            }
+        }
+        keymap.validate()?;
+        Ok(keymap)
+    }
+
+    // Two bindings conflict when they use the same keys, or when one is a
+    // prefix of the other since the longer one could never be reached.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for context in KeyContext::ALL {
+            let bindings = &self.bindings[context];
+            for (i, first) in bindings.iter().enumerate() {
+                for second in &bindings[i + 1..] {
+                    let (KeyPattern::Keys(a), KeyPattern::Keys(b)) = (&first.key, &second.key)
+                    else {
+                        continue;
+                    };
+                    if a.starts_with(b) || b.starts_with(a) {
+                        return Err(format!(
+                            "key conflict in [keys.{}]: '{}' ({}) and '{}' ({})",
+                            context.get_name(),
+                            first.key.get_name(),
+                            first.action.get_name(),
+                            second.key.get_name(),
+                            second.action.get_name()
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_bindings(&self, mode: &ScreenMode) -> &[Binding] {
+        &self.bindings[&KeyContext::from_mode(mode)]
+    }
+
+    // `pending` holds the keys typed so far, the last one included.
+    pub fn get_action(&self, mode: &ScreenMode, pending: &[KeyPress]) -> KeyLookup {
+        let mut lookup = KeyLookup::None;
+        for binding in self.get_bindings(mode) {
+            match &binding.key {
+                KeyPattern::Keys(keys) if keys == pending => {
+                    return KeyLookup::Action(binding.action.clone())
+                }
+                KeyPattern::Keys(keys) if keys.starts_with(pending) => lookup = KeyLookup::Pending,
+                KeyPattern::AnyChar
+                    if pending.len() == 1
+                        && matches!(pending[0].code, KeyCode::Char(_))
+                        && !pending[0].modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    if let (KeyLookup::None, KeyCode::Char(c)) = (&lookup, pending[0].code) {
+                        lookup = KeyLookup::Action(Action::InsertChar(c));
+                    }
+                }
+                _ => {}
+            }
+        }
+        lookup
+    }
+
+    // Bindings grouped by action, in keymap order, for the help popup.
+    pub fn get_help(&self, mode: &ScreenMode) -> Vec<(String, &'static str)> {
+        let mut help: Vec<(&Action, Vec<String>)> = vec![];
+        for binding in self.get_bindings(mode) {
+            match help
+                .iter_mut()
+                .find(|(action, _)| **action == binding.action)
+            {
+                Some((_, keys)) => keys.push(binding.key.get_name()),
+                None => help.push((&binding.action, vec![binding.key.get_name()])),
+            }
+        }
+        help.into_iter()
+            .map(|(action, keys)| (keys.join(", "), action.get_description()))
+            .collect()
+    }
+}
+
+// The help popup is not a key context: it scrolls with the arrows and `j`/`k`,
+// and any other key closes it.
+pub fn get_help_action(key: &KeyPress) -> Option<Action> {
+    match key.code {
+        _ if !key.modifiers.is_empty() => None,
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::PreviousItem),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::NextItem),
+        _ => None,
+    }
+}
+
+pub fn get_key_name(code: &KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::Up => String::from("Up"),
+        KeyCode::Down => String::from("Down"),
+        KeyCode::Left => String::from("Left"),
+        KeyCode::Right => String::from("Right"),
+        KeyCode::Enter => String::from("Enter"),
+        KeyCode::Esc => String::from("Esc"),
+        KeyCode::Backspace => String::from("Backspace"),
+        KeyCode::Tab => String::from("Tab"),
+        KeyCode::BackTab => String::from("Shift-Tab"),
+        KeyCode::Delete => String::from("Delete"),
+        KeyCode::Home => String::from("Home"),
+        KeyCode::End => String::from("End"),
+        KeyCode::PageUp => String::from("PageUp"),
+        KeyCode::PageDown => String::from("PageDown"),
+        code => format!("{code:?}"),
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod keymap_test {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyPress {
+        KeyPress::new(code)
+    }
+
+    fn lookup(keymap: &Keymap, mode: ScreenMode, keys: &[KeyPress]) -> Option<Action> {
+        match keymap.get_action(&mode, keys) {
+            KeyLookup::Action(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    fn overrides(context: &str, action: &str, keys: &[&str]) -> KeyOverrides {
+        HashMap::from([(
+            context.to_string(),
+            HashMap::from([(
+                action.to_string(),
+                keys.iter().map(|k| k.to_string()).collect(),
+            )]),
+        )])
+    }
+
+    #[test]
+    fn get_action() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            lookup(&keymap, ScreenMode::Main, &[press(KeyCode::Char('j'))]),
+            Some(Action::NextTask)
+        );
+        assert_eq!(
+            lookup(
+                &keymap,
+                ScreenMode::AddingTask,
+                &[press(KeyCode::Char('j'))]
+            ),
+            Some(Action::InsertChar('j'))
+        );
+        assert_eq!(
+            lookup(&keymap, ScreenMode::Main, &[press(KeyCode::Char('z'))]),
+            None
+        );
+    }
+
+    #[test]
+    fn sequences() {
+        let keymap = Keymap::default();
+        let g = press(KeyCode::Char('g'));
+
+        assert!(matches!(
+            keymap.get_action(&ScreenMode::Main, &[g]),
+            KeyLookup::Pending
+        ));
+        assert_eq!(
+            lookup(&keymap, ScreenMode::Main, &[g, g]),
+            Some(Action::FirstTask)
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            KeyPattern::parse("dd").unwrap(),
+            keys(&[KeyCode::Char('d'), KeyCode::Char('d')])
+        );
+        assert_eq!(
+            KeyPattern::parse("PageDown").unwrap(),
+            key(KeyCode::PageDown)
+        );
+        assert_eq!(
+            KeyPattern::parse("Ctrl-x C-s").unwrap(),
+            KeyPattern::Keys(vec![
+                KeyPress {
+                    code: KeyCode::Char('x'),
+                    modifiers: KeyModifiers::CONTROL
+                },
+                KeyPress {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL
+                },
+            ])
+        );
+        assert!(KeyPattern::parse("Ctrl-Foo").is_err());
+        assert_eq!(
+            KeyPattern::parse("Shift-g").unwrap(),
+            key(KeyCode::Char('G'))
+        );
+        assert_eq!(
+            KeyPattern::parse("gT").unwrap(),
+            keys(&[KeyCode::Char('g'), KeyCode::Char('T')])
+        );
+        assert!(KeyPattern::parse("Insert").is_err());
+        assert!(KeyPattern::parse("pgup").is_err());
+        assert!(KeyPattern::parse(" ").is_err());
+    }
+
+    #[test]
+    fn build() {
+        let keymap = Keymap::build(&overrides("main", "delete_task", &["dd", "Delete"])).unwrap();
+        let d = press(KeyCode::Char('d'));
+
+        assert_eq!(
+            lookup(&keymap, ScreenMode::Main, &[d, d]),
+            Some(Action::DeleteTask)
+        );
+        assert_eq!(
+            lookup(&keymap, ScreenMode::Main, &[press(KeyCode::Delete)]),
+            Some(Action::DeleteTask)
+        );
+        assert!(matches!(
+            keymap.get_action(&ScreenMode::Main, &[d]),
+            KeyLookup::Pending
+        ));
+    }
+
+    #[test]
+    fn conflicts() {
+        assert!(Keymap::build(&overrides("main", "quit", &["j"])).is_err());
+        assert!(Keymap::build(&overrides("main", "quit", &["g"])).is_err());
+        assert!(Keymap::build(&overrides("main", "quit", &["q", "q"])).is_err());
+        assert!(Keymap::build(&overrides("main", "frobnicate", &["x"])).is_err());
+        assert!(Keymap::build(&overrides("prompt", "insert_char", &["x"])).is_ok());
+        let error = Keymap::build(&overrides("nowhere", "quit", &["x"]))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("confirm, archive"));
+        assert!(Keymap::build(&overrides("main", "quit", &["x"])).is_ok());
+    }
+
+    #[test]
+    fn get_help() {
+        let help = Keymap::default().get_help(&ScreenMode::Main);
+
+        assert!(help.contains(&(String::from("Up, k"), "Previous task")));
+        assert!(help.contains(&(String::from("?, F1"), "Show this help")));
+        assert!(help.contains(&(String::from("gg, Home"), "First task")));
+        assert_eq!(help.len(), 41);
+    }
+}
@@ -1,23 +1,77 @@
-use comps::CursorDirection;
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::{
-    prelude::{Backend, CrosstermBackend},
-    Terminal,
-};
-use std::{error::Error, io::Stderr};
+use crossterm::event::{self, Event, MouseEventKind};
+use ratatui::{prelude::CrosstermBackend, Terminal};
+use std::{env, error::Error, io::Stderr, process, time::Instant};
 
-use app::{App, ScreenMode, SwitchProjectsDirection, TaskNavDirection};
+use action::Action;
+use app::App;
+use config::Config;
+use keymap::{get_help_action, KeyLookup, KeyPress, Keymap};
 use ui::ui;
 use utils::{cleanup_terminal, init_terminal};
+use vi::Vi;
 
+mod action;
 mod app;
+mod archive;
+mod batch;
+mod cli;
 mod comps;
+mod config;
+mod date;
+mod editor;
+mod history;
+mod keymap;
+mod mouse;
+mod org;
+mod output;
+mod palette;
+mod query;
+mod report;
+mod search;
+#[cfg(test)]
+mod testing;
+mod theme;
 mod ui;
 mod utils;
+mod vi;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut terminal = init_terminal::<CrosstermBackend<Stderr>>()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        let code = match cli::run(&args) {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("{e}");
+                cli::EXIT_ERROR
+            }
+        };
+        process::exit(code);
+    }
+
+    let settings = Config::load().and_then(|config| {
+        let keymap = Keymap::build(&config.keys)?;
+        let theme = config.get_theme()?;
+        Ok((config, keymap, theme))
+    });
+    let (config, keymap, theme) = match settings {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Invalid config: {e}");
+            process::exit(cli::EXIT_ERROR);
+        }
+    };
+
+    let mut terminal = init_terminal()?;
     let mut app = App::build()?;
+    app.keymap = keymap;
+    app.theme = theme;
+    app.show_sidebar = config.sidebar;
+    app.show_dates = config.show_dates;
+    app.vi = config.vi_mode.then(Vi::new);
+    app.hide_default = config.hide_tasks;
+    app.archive_after_days = config.archive_after_days;
+    app.apply_archive_policy(date::now())?;
+    app.init_current_task_id();
     run_app(&mut terminal, &mut app)?;
     cleanup_terminal(&mut terminal)?;
 
@@ -27,108 +81,81 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
+pub fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
     app: &mut App,
 ) -> Result<(), Box<dyn Error>> {
     loop {
         terminal.draw(|f| ui(f, app))?;
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            if app.show_help {
+                match mouse.kind {
+                    MouseEventKind::ScrollUp => app.dispatch(Action::PreviousItem)?,
+                    MouseEventKind::ScrollDown => app.dispatch(Action::NextItem)?,
+                    MouseEventKind::Moved => {}
+                    _ => app.show_help = false,
+                }
+                continue;
+            }
+            let actions = mouse::get_actions(app, &mouse, Instant::now());
+            if !actions.is_empty() {
+                app.message = None;
+            }
+            for action in actions {
+                app.dispatch(action)?;
+            }
+            continue;
+        }
+        if let Event::Paste(text) = event {
+            if !app.show_help {
+                app.dispatch(Action::Paste(text))?;
+            }
+            continue;
+        }
+        if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Release {
                 continue;
             }
-            match app.screen_mode {
-                ScreenMode::Main => match key.code {
-                    KeyCode::Char('q') => {
-                        app.save_file()?;
-                        break;
-                    }
-                    KeyCode::Left => app.switch_project(SwitchProjectsDirection::Left),
-                    KeyCode::Right => app.switch_project(SwitchProjectsDirection::Right),
-                    KeyCode::Up | KeyCode::Char('k') => app.nav_tasks(TaskNavDirection::Up),
-                    KeyCode::Down | KeyCode::Char('j') => app.nav_tasks(TaskNavDirection::Down),
-                    KeyCode::Enter => app.toggle_task_state(),
-                    KeyCode::Char('c') => app.cancel_task(),
-                    KeyCode::Char('a') => app.screen_mode = ScreenMode::AddingTask,
-                    KeyCode::Char('r') => {
-                        app.screen_mode = ScreenMode::RenamingTask;
-                        app.task_to_cursor_manager();
-                    }
-                    KeyCode::Char('d') => {
-                        app.screen_mode = ScreenMode::DeletingTask;
-                    }
-                    KeyCode::Char('n') => {
-                        app.add_project();
-                        app.screen_mode = ScreenMode::RenamingProject;
-                    }
-                    KeyCode::Char('p') => {
-                        app.screen_mode = ScreenMode::RenamingProject;
-                        app.project_to_cursor_manager();
-                    }
-                    _ => {}
-                },
-                ScreenMode::AddingTask => match key.code {
-                    KeyCode::Esc => {
-                        app.screen_mode = ScreenMode::Main;
-                        app.cursor_manager.clear();
-                    }
-                    KeyCode::Char(char) => app.cursor_manager.insert(char),
-                    KeyCode::Backspace => app.cursor_manager.delete(),
-                    KeyCode::Enter => {
-                        let new_task = app.cursor_manager.validate();
-                        app.add_task(new_task);
-                        app.screen_mode = ScreenMode::Main;
-                        app.save_file()?;
-                    }
-                    KeyCode::Right => app.cursor_manager.move_cursor(CursorDirection::Right),
-                    KeyCode::Left => app.cursor_manager.move_cursor(CursorDirection::Left),
-                    _ => {}
-                },
-                ScreenMode::RenamingTask => match key.code {
-                    KeyCode::Esc => {
-                        app.screen_mode = ScreenMode::Main;
-                        app.cursor_manager.clear();
-                    }
-                    KeyCode::Char(char) => app.cursor_manager.insert(char),
-                    KeyCode::Enter => {
-                        let new_task_name = app.cursor_manager.validate();
-                        app.rename_task(new_task_name);
-                        app.screen_mode = ScreenMode::Main;
-                        app.save_file()?;
-                    }
-                    KeyCode::Backspace => app.cursor_manager.delete(),
-                    KeyCode::Right => app.cursor_manager.move_cursor(CursorDirection::Right),
-                    KeyCode::Left => app.cursor_manager.move_cursor(CursorDirection::Left),
-                    _ => {}
-                },
-                ScreenMode::RenamingProject => match key.code {
-                    KeyCode::Esc => {
-                        app.screen_mode = ScreenMode::Main;
-                        app.cursor_manager.clear();
-                    }
-                    KeyCode::Char(char) => app.cursor_manager.insert(char),
-                    KeyCode::Enter => {
-                        let new_project_name = app.cursor_manager.validate();
-                        app.rename_project(new_project_name);
-                        app.screen_mode = ScreenMode::Main;
-                        app.save_file()?;
-                    }
-                    KeyCode::Backspace => app.cursor_manager.delete(),
-                    KeyCode::Right => app.cursor_manager.move_cursor(CursorDirection::Right),
-                    KeyCode::Left => app.cursor_manager.move_cursor(CursorDirection::Left),
-                    _ => {}
-                },
-                ScreenMode::DeletingTask => match key.code {
-                    KeyCode::Esc | KeyCode::Char('n') => {
-                        app.screen_mode = ScreenMode::Main;
-                    }
-                    KeyCode::Char('y') => {
-                        app.delete_task(app.current_task_id);
-                        app.save_file()?;
-                        app.screen_mode = ScreenMode::Main;
+            if app.show_help {
+                match get_help_action(&KeyPress::from_event(&key)) {
+                    Some(action) => app.dispatch(action)?,
+                    None => app.show_help = false,
+                }
+                continue;
+            }
+            app.message = None;
+            app.pending_keys.push(KeyPress::from_event(&key));
+            let action = match app.keymap.get_action(&app.screen_mode, &app.pending_keys) {
+                KeyLookup::Action(action) => action,
+                KeyLookup::Pending => continue,
+                KeyLookup::None => {
+                    // Abandon an unfinished sequence and retry with the last key alone.
+                    let retry = app.pending_keys.len() > 1;
+                    app.pending_keys = vec![KeyPress::from_event(&key)];
+                    match app.keymap.get_action(&app.screen_mode, &app.pending_keys) {
+                        KeyLookup::Action(action) if retry => action,
+                        KeyLookup::Pending if retry => continue,
+                        _ => {
+                            app.pending_keys.clear();
+                            continue;
+                        }
                     }
-                    _ => {}
-                },
+                }
+            };
+            app.pending_keys.clear();
+            app.dispatch(action)?;
+            if let Some(document) = app.editor_document.take() {
+                cleanup_terminal(terminal)?;
+                let edited = editor::open(&document);
+                *terminal = init_terminal()?;
+                match edited {
+                    Ok(document) => app.dispatch(Action::ApplyEdit(document))?,
+                    Err(e) => app.message = Some(format!("Task not changed, {e}")),
+                }
+            }
+            if app.should_quit {
+                break;
             }
         }
     }
@@ -0,0 +1,158 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use std::time::{Duration, Instant};
+
+use crate::{
+    action::Action,
+    app::{App, ScreenMode},
+};
+
+// Mouse stuff
+//
+// `ui` records what it draws where in `App::hit_areas`, and mouse events are
+// turned into the same actions as the keys.
+
+const DOUBLE_CLICK_DELAY: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Task(u32),
+    Checkbox(u32),
+    Project(u32),
+    PreviousProject,
+    NextProject,
+    Tasks,
+    Sidebar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitArea {
+    pub area: Rect,
+    pub target: Target,
+}
+
+// Areas recorded later are drawn on top, so they win.
+pub fn get_target(app: &App, column: u16, row: u16) -> Option<Target> {
+    app.hit_areas
+        .iter()
+        .rev()
+        .find(|hit| hit.area.contains(Position::new(column, row)))
+        .map(|hit| hit.target)
+}
+
+pub fn get_actions(app: &mut App, event: &MouseEvent, now: Instant) -> Vec<Action> {
+    if !matches!(
+        app.screen_mode,
+        ScreenMode::Main | ScreenMode::Board | ScreenMode::Sidebar
+    ) {
+        return vec![];
+    }
+    let Some(target) = get_target(app, event.column, event.row) else {
+        return vec![];
+    };
+    let in_sidebar = matches!(target, Target::Project(_) | Target::Sidebar);
+    let mut actions = vec![];
+
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let double_click = app.last_click.is_some_and(|(time, last)| {
+                last == target && now.duration_since(time) < DOUBLE_CLICK_DELAY
+            });
+            app.last_click = if double_click {
+                None
+            } else {
+                Some((now, target))
+            };
+
+            if app.screen_mode == ScreenMode::Sidebar
+                && matches!(target, Target::Task(_) | Target::Checkbox(_))
+            {
+                actions.push(Action::SwitchFocus);
+            }
+            match target {
+                Target::Task(id) => {
+                    actions.push(Action::SelectTask(id));
+                    if double_click {
+                        actions.push(Action::ToggleTask);
+                    }
+                }
+                Target::Checkbox(id) => {
+                    actions.push(Action::SelectTask(id));
+                    actions.push(Action::ToggleTask);
+                }
+                Target::Project(id) => actions.push(Action::SelectProject(id)),
+                Target::PreviousProject => actions.push(Action::PreviousProject),
+                Target::NextProject => actions.push(Action::NextProject),
+                Target::Tasks | Target::Sidebar => {}
+            }
+        }
+        MouseEventKind::ScrollDown if in_sidebar => actions.push(Action::NextProject),
+        MouseEventKind::ScrollUp if in_sidebar => actions.push(Action::PreviousProject),
+        MouseEventKind::ScrollDown => actions.push(Action::NextTask),
+        MouseEventKind::ScrollUp => actions.push(Action::PreviousTask),
+        _ => {}
+    }
+    actions
+}
+
+// Tests
+
+#[cfg(test)]
+mod mouse_test {
+    use super::*;
+    use crate::{
+        comps::TaskState,
+        testing::{build_app, TempFiles},
+        ui::ui,
+    };
+    use crossterm::event::KeyModifiers;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn build(name: &str) -> (App, Terminal<TestBackend>, TempFiles) {
+        let (mut app, files) = build_app(&format!("mouse_{name}"));
+        app.show_sidebar = false;
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        (app, terminal, files)
+    }
+
+    fn click(app: &mut App, column: u16, row: u16, now: Instant) {
+        let event = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        for action in get_actions(app, &event, now) {
+            app.dispatch(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn click_tasks() {
+        let (mut app, _terminal, _files) = build("click_tasks");
+        let now = Instant::now();
+
+        // Second row of the list, on the title.
+        click(&mut app, 20, 2, now);
+        assert_eq!(app.current_task_id, 2);
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Todo));
+
+        click(&mut app, 20, 2, now + Duration::from_millis(100));
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Completed));
+
+        // The checkbox toggles with a single click.
+        click(&mut app, 2, 1, now + Duration::from_secs(2));
+        assert_eq!(app.current_task_id, 1);
+        assert_eq!(app.get_current_task_state(), Some(TaskState::Completed));
+    }
+
+    #[test]
+    fn click_arrows() {
+        let (mut app, _terminal, _files) = build("click_arrows");
+
+        assert_eq!(get_target(&app, 2, 0), Some(Target::PreviousProject));
+        click(&mut app, 2, 0, Instant::now());
+        assert_eq!(app.get_current_project_name(), "Other Project");
+    }
+}
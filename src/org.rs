@@ -0,0 +1,300 @@
+use crate::comps::{Project, TaskState};
+
+// Org-mode conversion stuff
+//
+// Top-level headings are projects, every deeper heading is a task of the
+// enclosing project: tuido has no subtasks, so nesting is flattened and
+// tasks are exported back at level 2. Heading tags (`:a:b:`) become `#a #b`
+// tags at the end of the title, and the body of a task heading its notes.
+// Priority cookies, property drawers and planning lines are dropped.
+
+const DEFAULT_PROJECT_NAME: &str = "Imported";
+
+pub fn from_org(content: &str, first_project_id: u32, first_task_id: u32) -> Vec<Project> {
+    let mut projects: Vec<Project> = vec![];
+    let mut next_project_id = first_project_id;
+    let mut next_task_id = first_task_id;
+
+    // Whether body lines belong to the last task, and if inside a drawer.
+    let mut in_task = false;
+    let mut in_drawer = false;
+
+    for line in content.lines() {
+        let Some((level, heading)) = parse_heading(line) else {
+            if in_task {
+                let task = projects.last_mut().unwrap().tasks.last_mut().unwrap();
+                add_body_line(&mut task.notes, line, &mut in_drawer);
+            }
+            continue;
+        };
+        in_drawer = false;
+        if let Some(task) = projects.last_mut().and_then(|p| p.tasks.last_mut()) {
+            task.notes = task.notes.trim_end().to_string();
+        }
+
+        if level == 1 {
+            projects.push(Project::new(next_project_id, heading.to_string()));
+            next_project_id += 1;
+            in_task = false;
+            continue;
+        }
+
+        if projects.is_empty() {
+            projects.push(Project::new(
+                next_project_id,
+                String::from(DEFAULT_PROJECT_NAME),
+            ));
+            next_project_id += 1;
+        }
+
+        let (state, title) = parse_keyword(heading);
+        let project = projects.last_mut().unwrap();
+        project.add_task(next_task_id, get_title(title));
+        project.tasks.last_mut().unwrap().set_state(state);
+        next_task_id += 1;
+        in_task = true;
+    }
+    if let Some(task) = projects.last_mut().and_then(|p| p.tasks.last_mut()) {
+        task.notes = task.notes.trim_end().to_string();
+    }
+
+    projects
+}
+
+pub fn to_org(projects: &[Project]) -> String {
+    let mut org = String::new();
+    for project in projects {
+        org.push_str(&format!("* {}\n", project.name));
+        for task in &project.tasks {
+            let (title, tags) = split_tags(&task.title);
+            org.push_str(&format!("** {}", get_keyword(&task.state)));
+            if !title.is_empty() {
+                org.push_str(&format!(" {title}"));
+            }
+            if !tags.is_empty() {
+                org.push_str(&format!(" :{}:", tags.join(":")));
+            }
+            org.push('\n');
+            // Indented, so that a note line starting with `*` stays a note.
+            for line in task.notes.lines() {
+                if line.is_empty() {
+                    org.push('\n');
+                } else {
+                    org.push_str(&format!("   {line}\n"));
+                }
+            }
+        }
+    }
+    org
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+fn add_body_line(notes: &mut String, line: &str, in_drawer: &mut bool) {
+    let line = line.trim();
+    if *in_drawer {
+        *in_drawer = !line.eq_ignore_ascii_case(":END:");
+        return;
+    }
+    if line.starts_with(':') && line.ends_with(':') && line.len() > 1 {
+        *in_drawer = true;
+        return;
+    }
+    let planning = ["SCHEDULED:", "DEADLINE:", "CLOSED:"];
+    if planning.iter().any(|p| line.starts_with(p)) {
+        return;
+    }
+    if notes.is_empty() && line.is_empty() {
+        return;
+    }
+    if !notes.is_empty() {
+        notes.push('\n');
+    }
+    notes.push_str(line);
+}
+
+// The task title of a heading without its keyword: the priority cookie is
+// dropped and the org tags are appended as `#tags`.
+fn get_title(heading: &str) -> String {
+    let heading = match heading.split_once(' ') {
+        Some((cookie, rest)) if is_priority(cookie) => rest.trim_start(),
+        None if is_priority(heading) => "",
+        _ => heading,
+    };
+    let (title, tags) = match heading.rsplit_once(' ') {
+        Some((title, tags)) if is_org_tags(tags) => (title.trim_end(), tags),
+        None if is_org_tags(heading) => ("", heading),
+        _ => (heading, ""),
+    };
+    let mut title = title.to_string();
+    for tag in tags.split(':').filter(|t| !t.is_empty()) {
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push('#');
+        title.push_str(tag);
+    }
+    title
+}
+
+fn is_priority(word: &str) -> bool {
+    word.len() == 4 && word.starts_with("[#") && word.ends_with(']')
+}
+
+fn is_org_tags(word: &str) -> bool {
+    word.len() > 2
+        && word.starts_with(':')
+        && word.ends_with(':')
+        && word[1..word.len() - 1]
+            .split(':')
+            .all(|tag| !tag.is_empty() && tag.chars().all(is_tag_char))
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '@' | '#' | '%')
+}
+
+// The `#tags` ending a title, exported as org tags.
+fn split_tags(title: &str) -> (&str, Vec<&str>) {
+    let mut rest = title.trim_end();
+    let mut tags = vec![];
+    while let Some((head, word)) = rest.rsplit_once(' ') {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() && tag.chars().all(is_tag_char) => {
+                tags.insert(0, tag);
+                rest = head.trim_end();
+            }
+            _ => break,
+        }
+    }
+    (rest, tags)
+}
+
+fn parse_keyword(heading: &str) -> (TaskState, &str) {
+    let (keyword, rest) = match heading.split_once(' ') {
+        Some((keyword, rest)) => (keyword, rest.trim_start()),
+        None => (heading, ""),
+    };
+    match keyword {
+        "TODO" => (TaskState::Todo, rest),
+        "DONE" => (TaskState::Completed, rest),
+        "CANCELED" | "CANCELLED" => (TaskState::Canceled, rest),
+        _ => (TaskState::Todo, heading),
+    }
+}
+
+fn get_keyword(state: &TaskState) -> &'static str {
+    match state {
+        TaskState::Todo => "TODO",
+        TaskState::Completed => "DONE",
+        TaskState::Canceled => "CANCELED",
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod org_test {
+    use super::*;
+
+    #[test]
+    fn from_org() {
+        let org = "#+TITLE: Work\n\
+                   * Backend\n\
+                   ** TODO Fix login\n\
+                   Some notes\n\
+                   ** DONE Deploy\n\
+                   *** CANCELLED Rollback plan\n\
+                   * Frontend\n\
+                   ** Plain heading\n";
+
+        let projects = super::from_org(org, 3, 10);
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].id, 3);
+        assert_eq!(projects[0].name, "Backend");
+        assert_eq!(projects[0].tasks.len(), 3);
+        assert_eq!(projects[0].tasks[0].id, 10);
+        assert_eq!(projects[0].tasks[0].title, "Fix login");
+        assert_eq!(projects[0].tasks[0].notes, "Some notes");
+        assert!(matches!(projects[0].tasks[1].state, TaskState::Completed));
+        assert!(matches!(projects[0].tasks[2].state, TaskState::Canceled));
+        assert_eq!(projects[0].tasks[2].title, "Rollback plan");
+        assert_eq!(projects[1].tasks[0].title, "Plain heading");
+        assert!(matches!(projects[1].tasks[0].state, TaskState::Todo));
+    }
+
+    #[test]
+    fn orphan_tasks() {
+        let projects = super::from_org("** TODO Lost task\n", 1, 1);
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, DEFAULT_PROJECT_NAME);
+        assert_eq!(projects[0].tasks[0].title, "Lost task");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut project = Project::new(1, String::from("Projet 1"));
+        project.add_task(1, String::from("Bonjour"));
+        project.add_task(2, String::from("Salut"));
+        project.toggle_task_state(2);
+
+        let org = to_org(&[project]);
+        assert_eq!(org, "* Projet 1\n** TODO Bonjour\n** DONE Salut\n");
+
+        let projects = super::from_org(&org, 1, 1);
+        assert_eq!(to_org(&projects), org);
+    }
+
+    // What survives an import followed by an export: projects, states,
+    // titles, tags and notes. Nesting, priorities, drawers and planning
+    // lines do not.
+    #[test]
+    fn round_trip_org() {
+        let org = "* Backend\n\
+                   ** TODO [#A] Fix login :bug:urgent:\n\
+                   SCHEDULED: <2024-05-01 Wed>\n\
+                   :PROPERTIES:\n\
+                   :ID: 42\n\
+                   :END:\n\
+                   \x20  Check the *session* cookie.\n\
+                   \n\
+                   \x20  * not a heading\n\
+                   *** DONE Write test :test:\n\
+                   ** [#B]\n";
+
+        let projects = super::from_org(org, 1, 1);
+        let tasks = &projects[0].tasks;
+        assert_eq!(tasks[0].title, "Fix login #bug #urgent");
+        assert_eq!(
+            tasks[0].notes,
+            "Check the *session* cookie.\n\n* not a heading"
+        );
+        assert_eq!(tasks[1].title, "Write test #test");
+        assert_eq!(tasks[2].title, "");
+
+        let exported = to_org(&projects);
+        assert_eq!(
+            exported,
+            "* Backend\n\
+             ** TODO Fix login :bug:urgent:\n\
+             \x20  Check the *session* cookie.\n\
+             \n\
+             \x20  * not a heading\n\
+             ** DONE Write test :test:\n\
+             ** TODO\n"
+        );
+        assert_eq!(to_org(&super::from_org(&exported, 1, 1)), exported);
+    }
+}
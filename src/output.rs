@@ -0,0 +1,277 @@
+use serde::Serialize;
+use std::error::Error;
+
+use crate::{
+    comps::{Project, Task, TaskState},
+    ui::Stats,
+};
+
+// Output stuff
+//
+// Records printed by the list commands. They are decoupled from the storage
+// layout in `tasks.json`: any change to their fields must bump
+// `SCHEMA_VERSION` (see the ReadMe for the documented schema).
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    Tsv,
+    Json,
+    Jsonl,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Self, Box<dyn Error>> {
+        match format {
+            "plain" => Ok(Format::Plain),
+            "tsv" => Ok(Format::Tsv),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            _ => Err(
+                format!("unknown format '{format}' (expected json, jsonl, tsv or plain)").into(),
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TaskRecord<'a> {
+    pub version: u32,
+    pub id: u32,
+    pub project_id: u32,
+    pub project: &'a str,
+    pub state: &'static str,
+    pub title: &'a str,
+}
+
+impl<'a> TaskRecord<'a> {
+    pub fn new(project: &'a Project, task: &'a Task) -> Self {
+        TaskRecord {
+            version: SCHEMA_VERSION,
+            id: task.id,
+            project_id: project.id,
+            project: &project.name,
+            state: get_state_name(&task.state),
+            title: &task.title,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProjectRecord<'a> {
+    pub version: u32,
+    pub id: u32,
+    pub name: &'a str,
+    pub current: bool,
+    pub tasks: u32,
+    pub todo: u32,
+    pub completed: u32,
+    pub canceled: u32,
+}
+
+impl<'a> ProjectRecord<'a> {
+    pub fn new(project: &'a Project) -> Self {
+        let mut stats = Stats::new();
+        for task in &project.tasks {
+            stats.add_task(&task.state);
+        }
+        ProjectRecord {
+            version: SCHEMA_VERSION,
+            id: project.id,
+            name: &project.name,
+            current: project.is_current,
+            tasks: stats.tasks,
+            todo: stats.todo(),
+            completed: stats.completed,
+            canceled: stats.canceled,
+        }
+    }
+}
+
+pub fn format_tasks(records: &[TaskRecord], format: Format) -> Result<String, Box<dyn Error>> {
+    match format {
+        Format::Plain => Ok(records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{:>4} {} {} ({})\n",
+                    r.id,
+                    get_plain_box(r.state),
+                    r.title,
+                    r.project
+                )
+            })
+            .collect()),
+        Format::Tsv => Ok(format_tsv(
+            &["version", "id", "project_id", "project", "state", "title"],
+            records.iter().map(|r| {
+                vec![
+                    r.version.to_string(),
+                    r.id.to_string(),
+                    r.project_id.to_string(),
+                    r.project.to_string(),
+                    r.state.to_string(),
+                    r.title.to_string(),
+                ]
+            }),
+        )),
+        Format::Json => Ok(serde_json::to_string_pretty(records)? + "\n"),
+        Format::Jsonl => format_jsonl(records),
+    }
+}
+
+pub fn format_projects(
+    records: &[ProjectRecord],
+    format: Format,
+) -> Result<String, Box<dyn Error>> {
+    match format {
+        Format::Plain => Ok(records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{:>4} {}{} ({} open, {} completed, {} canceled)\n",
+                    r.id,
+                    r.name,
+                    if r.current { " *" } else { "" },
+                    r.todo,
+                    r.completed,
+                    r.canceled
+                )
+            })
+            .collect()),
+        Format::Tsv => Ok(format_tsv(
+            &[
+                "version",
+                "id",
+                "name",
+                "current",
+                "tasks",
+                "todo",
+                "completed",
+                "canceled",
+            ],
+            records.iter().map(|r| {
+                vec![
+                    r.version.to_string(),
+                    r.id.to_string(),
+                    r.name.to_string(),
+                    r.current.to_string(),
+                    r.tasks.to_string(),
+                    r.todo.to_string(),
+                    r.completed.to_string(),
+                    r.canceled.to_string(),
+                ]
+            }),
+        )),
+        Format::Json => Ok(serde_json::to_string_pretty(records)? + "\n"),
+        Format::Jsonl => format_jsonl(records),
+    }
+}
+
+pub fn get_state_name(state: &TaskState) -> &'static str {
+    match state {
+        TaskState::Todo => "todo",
+        TaskState::Completed => "completed",
+        TaskState::Canceled => "canceled",
+    }
+}
+
+fn get_plain_box(state: &str) -> &'static str {
+    match state {
+        "completed" => "[x]",
+        "canceled" => "[-]",
+        _ => "[ ]",
+    }
+}
+
+fn format_jsonl<T: Serialize>(records: &[T]) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn format_tsv(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = header.join("\t");
+    out.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|f| escape_tsv(f)).collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+// Tests
+
+#[cfg(test)]
+mod output_test {
+    use super::*;
+
+    fn project() -> Project {
+        let mut project = Project::new(1, String::from("Projet 1"));
+        project.add_task(1, String::from("Bonjour"));
+        project.add_task(2, String::from("Salut\tà tous"));
+        project.toggle_task_state(2);
+        project
+    }
+
+    #[test]
+    fn tasks_tsv() {
+        let project = project();
+        let records: Vec<TaskRecord> = project
+            .tasks
+            .iter()
+            .map(|t| TaskRecord::new(&project, t))
+            .collect();
+
+        let tsv = format_tasks(&records, Format::Tsv).unwrap();
+        let lines: Vec<&str> = tsv.lines().collect();
+
+        assert_eq!(lines[0], "version\tid\tproject_id\tproject\tstate\ttitle");
+        assert_eq!(lines[2], "1\t2\t1\tProjet 1\tcompleted\tSalut\\tà tous");
+    }
+
+    #[test]
+    fn tasks_jsonl() {
+        let project = project();
+        let records: Vec<TaskRecord> = project
+            .tasks
+            .iter()
+            .map(|t| TaskRecord::new(&project, t))
+            .collect();
+
+        let jsonl = format_tasks(&records, Format::Jsonl).unwrap();
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+
+        assert_eq!(jsonl.lines().count(), 2);
+        assert_eq!(first["version"], SCHEMA_VERSION);
+        assert_eq!(first["state"], "todo");
+        assert_eq!(first["project"], "Projet 1");
+    }
+
+    #[test]
+    fn projects_json() {
+        let project = project();
+        let records = vec![ProjectRecord::new(&project)];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_projects(&records, Format::Json).unwrap()).unwrap();
+
+        assert_eq!(json[0]["tasks"], 2);
+        assert_eq!(json[0]["todo"], 1);
+        assert_eq!(json[0]["completed"], 1);
+    }
+}
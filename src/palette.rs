@@ -0,0 +1,170 @@
+use crate::{
+    action::Action,
+    app::{App, ScreenMode, EXPORT_ORG_EXTENSION, EXPORT_REPORT_EXTENSION},
+    comps::TaskState,
+};
+
+// Command palette stuff
+
+pub struct PaletteEntry {
+    pub label: String,
+    pub preview: String,
+    pub action: Action,
+}
+
+// Actions that make sense from the task list, in the order the palette
+// shows them when nothing is typed.
+const ACTIONS: &[Action] = &[
+    Action::AddTask,
+    Action::RenameTask,
+    Action::EditTask,
+    Action::DeleteTask,
+    Action::MarkAll,
+    Action::MarkSameState,
+    Action::ClearMarks,
+    Action::MoveTasks,
+    Action::TagTasks,
+    Action::Undo,
+    Action::ToggleTask,
+    Action::CompleteTask,
+    Action::CancelTask,
+    Action::FirstTask,
+    Action::LastTask,
+    Action::NewProject,
+    Action::RenameProject,
+    Action::ToggleSidebar,
+    Action::ToggleBoard,
+    Action::CycleSort,
+    Action::ReverseSort,
+    Action::ToggleGrouping,
+    Action::CycleHidden,
+    Action::ArchiveDone,
+    Action::ShowArchive,
+    Action::ToggleDates,
+    Action::PreviousProject,
+    Action::NextProject,
+    Action::Filter,
+    Action::ClearFilter,
+    Action::Search,
+    Action::ExportOrg,
+    Action::ExportReport,
+    Action::Help,
+    Action::Quit,
+];
+
+pub fn get_entries(app: &App) -> Vec<PaletteEntry> {
+    let bindings = app.keymap.get_bindings(&ScreenMode::Main);
+    let mut entries = vec![];
+
+    for action in ACTIONS {
+        if *action == Action::ClearFilter && app.filter.is_none() {
+            continue;
+        }
+        let keys: Vec<String> = bindings
+            .iter()
+            .filter(|b| b.action == *action)
+            .map(|b| b.key.get_name())
+            .collect();
+        let preview = match action {
+            Action::ExportOrg => format!(
+                "Write every project to {}",
+                app.file_manager
+                    .get_export_path(EXPORT_ORG_EXTENSION)
+                    .display()
+            ),
+            Action::ExportReport => format!(
+                "Write an HTML report to {}",
+                app.file_manager
+                    .get_export_path(EXPORT_REPORT_EXTENSION)
+                    .display()
+            ),
+            Action::ClearFilter => format!("Current filter: {}", app.filter_string),
+            _ if keys.is_empty() => String::from("No key binding"),
+            _ => format!("Keys: {}", keys.join(", ")),
+        };
+        entries.push(PaletteEntry {
+            label: action.get_description().to_string(),
+            preview,
+            action: action.clone(),
+        });
+    }
+
+    for project in &app.projects {
+        if project.id == app.current_project_id {
+            continue;
+        }
+        let open = project
+            .tasks
+            .iter()
+            .filter(|t| t.state == TaskState::Todo)
+            .count();
+        entries.push(PaletteEntry {
+            label: format!("Switch to project: {}", project.name),
+            preview: format!("{} task(s), {} open", project.tasks.len(), open),
+            action: Action::SelectProject(project.id),
+        });
+    }
+
+    entries
+}
+
+// Entries matching `query`, best match first.
+pub fn get_matches(app: &App, query: &str) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(i32, PaletteEntry)> = get_entries(app)
+        .into_iter()
+        .filter_map(|entry| fuzzy_score(query, &entry.label).map(|score| (score, entry)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+// Case insensitive subsequence match. Consecutive characters and characters
+// at the start of a word score higher; `None` when `query` does not match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|t| *t == c)?;
+        score += 1;
+        if previous_match == Some(index.wrapping_sub(1)) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (index - position) as i32 / 4;
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+// Tests
+
+#[cfg(test)]
+mod palette_test {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching() {
+        assert!(fuzzy_score("", "Add a task").is_some());
+        assert!(fuzzy_score("adtk", "Add a task").is_some());
+        assert!(fuzzy_score("ADD", "Add a task").is_some());
+        assert!(fuzzy_score("kat", "Add a task").is_none());
+    }
+
+    #[test]
+    fn ranking() {
+        let rename_project = fuzzy_score("renpro", "Rename project").unwrap();
+        let previous_project = fuzzy_score("renpro", "Previous project");
+
+        assert!(previous_project.is_none_or(|score| score < rename_project));
+        assert!(
+            fuzzy_score("del", "Delete task").unwrap()
+                > fuzzy_score("del", "Switch to project: Model").unwrap()
+        );
+    }
+}
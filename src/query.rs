@@ -0,0 +1,467 @@
+use std::{error::Error, fmt};
+
+use crate::comps::{Project, Task, TaskState};
+
+// Query stuff
+//
+// query   := or
+// or      := and ("or" and)*
+// and     := unary (["and"] unary)*
+// unary   := "not" unary | "(" or ")" | term
+// term    := field op value | word | "quoted text"
+// op      := ":" | "=" | "<" | "<=" | ">" | ">="
+//
+// Bare words and quoted text match task titles, case insensitively.
+
+const FIELDS: &str = "state, project, title, tag, id";
+
+#[derive(Debug, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {})", self.message, self.column)
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    State,
+    Project,
+    Title,
+    Tag,
+    Id,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Contains,
+    Equal,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Text(String),
+    State(TaskState),
+    Project(Op, String),
+    Title(Op, String),
+    Tag(String),
+    Id(Op, u32),
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    All,
+    Term(Term),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term(Term),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: query.chars().count() + 1,
+        };
+        let expr = if parser.tokens.is_empty() {
+            Expr::All
+        } else {
+            parser.parse_or()?
+        };
+        if let Some((_, column)) = parser.tokens.get(parser.position) {
+            return Err(QueryError {
+                message: String::from("unexpected ')'"),
+                column: *column,
+            });
+        }
+        Ok(Query { expr })
+    }
+
+    pub fn matches(&self, project: &Project, task: &Task) -> bool {
+        self.expr.matches(project, task)
+    }
+}
+
+impl Expr {
+    fn matches(&self, project: &Project, task: &Task) -> bool {
+        match self {
+            Expr::All => true,
+            Expr::Term(term) => term.matches(project, task),
+            Expr::Not(expr) => !expr.matches(project, task),
+            Expr::And(left, right) => left.matches(project, task) && right.matches(project, task),
+            Expr::Or(left, right) => left.matches(project, task) || right.matches(project, task),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, project: &Project, task: &Task) -> bool {
+        match self {
+            Term::Text(text) => contains(&task.title, text),
+            Term::State(state) => task.state == *state,
+            Term::Project(op, name) => compare_text(&project.name, *op, name),
+            Term::Title(op, title) => compare_text(&task.title, *op, title),
+            Term::Tag(tag) => task.tags().iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            Term::Id(op, id) => compare(&task.id, *op, id),
+        }
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn compare_text(value: &str, op: Op, expected: &str) -> bool {
+    match op {
+        Op::Contains => contains(value, expected),
+        _ => compare(&value.to_lowercase(), op, &expected.to_lowercase()),
+    }
+}
+
+fn compare<T: PartialOrd>(value: &T, op: Op, expected: &T) -> bool {
+    match op {
+        Op::Contains | Op::Equal => value == expected,
+        Op::Less => value < expected,
+        Op::LessEqual => value <= expected,
+        Op::Greater => value > expected,
+        Op::GreaterEqual => value >= expected,
+    }
+}
+
+// Tokenizer
+
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((Token::LParen, column));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::RParen, column));
+                i += 1;
+            }
+            '"' => {
+                let text = read_quoted(&chars, &mut i)?;
+                tokens.push((Token::Term(Term::Text(text)), column));
+            }
+            _ => {
+                let word = read_word(&chars, &mut i)?;
+                tokens.push((parse_word(word, column)?, column));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// `i` points at the opening quote and is left after the closing one.
+fn read_quoted(chars: &[char], i: &mut usize) -> Result<String, QueryError> {
+    let start = *i;
+    let Some(len) = chars[start + 1..].iter().position(|c| *c == '"') else {
+        return Err(QueryError {
+            message: String::from("unterminated quote"),
+            column: start + 1,
+        });
+    };
+    *i = start + len + 2;
+    Ok(chars[start + 1..start + 1 + len].iter().collect())
+}
+
+// A word runs until whitespace or a parenthesis. Quotes inside a word (as in
+// `project:"My project"`) are part of the value and may contain spaces.
+fn read_word(chars: &[char], i: &mut usize) -> Result<String, QueryError> {
+    let mut word = String::new();
+    while let Some(&c) = chars.get(*i) {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            break;
+        }
+        if c == '"' {
+            word.push_str(&read_quoted(chars, i)?);
+        } else {
+            word.push(c);
+            *i += 1;
+        }
+    }
+    Ok(word)
+}
+
+fn parse_word(word: String, column: usize) -> Result<Token, QueryError> {
+    match word.to_lowercase().as_str() {
+        "and" => return Ok(Token::And),
+        "or" => return Ok(Token::Or),
+        "not" => return Ok(Token::Not),
+        _ => {}
+    }
+
+    let Some(op_start) = word.find([':', '=', '<', '>']) else {
+        return Ok(Token::Term(Term::Text(word)));
+    };
+    let name = &word[..op_start];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Ok(Token::Term(Term::Text(word)));
+    }
+    let rest = &word[op_start..];
+    let (op, value) = if let Some(value) = rest.strip_prefix("<=") {
+        (Op::LessEqual, value)
+    } else if let Some(value) = rest.strip_prefix(">=") {
+        (Op::GreaterEqual, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Op::Less, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Op::Greater, value)
+    } else if let Some(value) = rest.strip_prefix('=') {
+        (Op::Equal, value)
+    } else {
+        (Op::Contains, &rest[1..])
+    };
+
+    let error = |message: String| QueryError { message, column };
+    let field = match name.to_lowercase().as_str() {
+        "state" => Field::State,
+        "project" => Field::Project,
+        "title" => Field::Title,
+        "tag" => Field::Tag,
+        "id" => Field::Id,
+        _ => {
+            return Err(error(format!(
+                "unknown field '{name}' (expected one of {FIELDS})"
+            )))
+        }
+    };
+    if value.is_empty() {
+        return Err(error(format!("missing value for '{name}'")));
+    }
+    let ordered = matches!(
+        op,
+        Op::Less | Op::LessEqual | Op::Greater | Op::GreaterEqual
+    );
+    if ordered && matches!(field, Field::State | Field::Tag) {
+        return Err(error(format!(
+            "'{name}' cannot be compared with '<' or '>'"
+        )));
+    }
+
+    let term = match field {
+        Field::State => Term::State(match value.to_lowercase().as_str() {
+            "todo" => TaskState::Todo,
+            "completed" | "done" => TaskState::Completed,
+            "canceled" | "cancelled" => TaskState::Canceled,
+            _ => {
+                return Err(error(format!(
+                    "unknown state '{value}' (expected todo, completed or canceled)"
+                )))
+            }
+        }),
+        Field::Project => Term::Project(op, value.to_string()),
+        Field::Title => Term::Title(op, value.to_string()),
+        Field::Tag => Term::Tag(value.trim_start_matches('#').to_string()),
+        Field::Id => Term::Id(
+            op,
+            value
+                .parse()
+                .map_err(|_| error(format!("invalid id '{value}'")))?,
+        ),
+    };
+    Ok(Token::Term(term))
+}
+
+// Parser
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map(|(_, column)| *column)
+            .unwrap_or(self.end)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.position += 1,
+                Some(Token::Not | Token::LParen | Token::Term(_)) => {}
+                _ => break,
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        let column = self.column();
+        match self.tokens.get(self.position) {
+            Some((Token::Not, _)) => {
+                self.position += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some((Token::LParen, _)) => {
+                self.position += 1;
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(QueryError {
+                        message: String::from("missing ')'"),
+                        column: self.column(),
+                    });
+                }
+                self.position += 1;
+                Ok(expr)
+            }
+            Some((Token::Term(term), _)) => {
+                let term = term.clone();
+                self.position += 1;
+                Ok(Expr::Term(term))
+            }
+            Some((Token::RParen, _)) => Err(QueryError {
+                message: String::from("unexpected ')'"),
+                column,
+            }),
+            Some((Token::And | Token::Or, _)) => Err(QueryError {
+                message: String::from("expected a term before 'and'/'or'"),
+                column,
+            }),
+            None => Err(QueryError {
+                message: String::from("unexpected end of query"),
+                column,
+            }),
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod query_test {
+    use super::*;
+
+    fn project() -> Project {
+        let mut project = Project::new(1, String::from("Backend"));
+        project.add_task(1, String::from("Fix flaky test #review"));
+        project.add_task(2, String::from("Write docs"));
+        project.add_task(3, String::from("Deploy #ops"));
+        project.toggle_task_state(3);
+        project
+    }
+
+    fn matching(query: &str) -> Vec<u32> {
+        let project = project();
+        let query = Query::parse(query).unwrap();
+        project
+            .tasks
+            .iter()
+            .filter(|task| query.matches(&project, task))
+            .map(|task| task.id)
+            .collect()
+    }
+
+    #[test]
+    fn empty_query() {
+        assert_eq!(matching(""), vec![1, 2, 3]);
+        assert_eq!(matching("   "), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(matching("flaky"), vec![1]);
+        assert_eq!(matching("\"WRITE docs\""), vec![2]);
+        assert_eq!(matching("fix test"), vec![1]);
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(matching("state:todo"), vec![1, 2]);
+        assert_eq!(matching("state:done"), vec![3]);
+        assert_eq!(matching("project:back"), vec![1, 2, 3]);
+        assert_eq!(matching("project=back"), Vec::<u32>::new());
+        assert_eq!(matching("project=\"BACKEND\""), vec![1, 2, 3]);
+        assert_eq!(matching("tag:review"), vec![1]);
+        assert_eq!(matching("tag:#ops"), vec![3]);
+        assert_eq!(matching("id>=2"), vec![2, 3]);
+        assert_eq!(matching("id<2"), vec![1]);
+        assert_eq!(matching("title:docs"), vec![2]);
+    }
+
+    #[test]
+    fn boolean() {
+        assert_eq!(matching("state:todo and not tag:review"), vec![2]);
+        assert_eq!(matching("tag:review or tag:ops"), vec![1, 3]);
+        assert_eq!(matching("state:todo (docs or deploy)"), vec![2]);
+        assert_eq!(matching("not (id=1 or id=2)"), vec![3]);
+        assert_eq!(matching("id=1 or id=2 id=2"), vec![1, 2]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = Query::parse("state:todo due<2026-11-01").unwrap_err();
+        assert_eq!(error.column, 12);
+        assert!(error.message.contains("unknown field 'due'"));
+
+        assert_eq!(
+            Query::parse("(state:todo").unwrap_err().message,
+            "missing ')'"
+        );
+        assert_eq!(Query::parse("state:todo)").unwrap_err().column, 11);
+        assert_eq!(
+            Query::parse("\"flaky").unwrap_err().message,
+            "unterminated quote"
+        );
+        assert!(Query::parse("state:later").is_err());
+        assert!(Query::parse("tag<a").is_err());
+        assert!(Query::parse("id:abc").is_err());
+        assert!(Query::parse("or flaky").is_err());
+        assert!(Query::parse("flaky and").is_err());
+        assert!(Query::parse("project:").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    comps::{Project, TaskState},
+    ui::Stats,
+};
+
+// HTML report stuff
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 50em; color: #222; }
+h1 { border-bottom: 2px solid #444; }
+section { margin-bottom: 2em; }
+ul { list-style: none; padding-left: 0; }
+li { padding: 0.2em 0.5em; border-left: 4px solid; margin-bottom: 0.2em; }
+li.todo { border-color: #3b82f6; }
+li.completed { border-color: #22c55e; color: #15803d; }
+li.canceled { border-color: #9ca3af; color: #6b7280; text-decoration: line-through; }
+.badges { margin: 0.5em 0; }
+.badge { display: inline-block; border-radius: 1em; padding: 0.1em 0.8em; margin-right: 0.4em; color: #fff; font-size: 0.9em; }
+.badge.tasks { background: #444; }
+.badge.todo { background: #3b82f6; }
+.badge.completed { background: #22c55e; }
+.badge.canceled { background: #9ca3af; }
+";
+
+pub fn to_html(projects: &[Project]) -> String {
+    let mut total = Stats::new();
+    let mut sections = String::new();
+
+    for project in projects {
+        let mut stats = Stats::new();
+        let mut items = String::new();
+        for task in &project.tasks {
+            stats.add_task(&task.state);
+            total.add_task(&task.state);
+            items.push_str(&format!(
+                "<li class=\"{}\">{}</li>\n",
+                get_class(&task.state),
+                escape(&task.title)
+            ));
+        }
+        sections.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n{}<ul>\n{}</ul>\n</section>\n",
+            escape(&project.name),
+            get_badges(&stats),
+            items
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>tuido report</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>tuido report</h1>\n{}{}</body>\n</html>\n",
+        get_badges(&total),
+        sections
+    )
+}
+
+fn get_badges(stats: &Stats) -> String {
+    format!(
+        "<div class=\"badges\">\
+         <span class=\"badge tasks\">Tasks: {}</span>\
+         <span class=\"badge todo\">Todo: {}</span>\
+         <span class=\"badge completed\">Completed: {}</span>\
+         <span class=\"badge canceled\">Canceled: {}</span>\
+         </div>\n",
+        stats.tasks,
+        stats.todo(),
+        stats.completed,
+        stats.canceled
+    )
+}
+
+fn get_class(state: &TaskState) -> &'static str {
+    match state {
+        TaskState::Todo => "todo",
+        TaskState::Completed => "completed",
+        TaskState::Canceled => "canceled",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Tests
+
+#[cfg(test)]
+mod report_test {
+    use super::*;
+
+    #[test]
+    fn to_html() {
+        let mut project = Project::new(1, String::from("Projet <1>"));
+        project.add_task(1, String::from("Bonjour"));
+        project.add_task(2, String::from("Salut & co"));
+        project.toggle_task_state(2);
+        project.add_task(3, String::from("Ciao"));
+        project.cancel_task(3);
+
+        let html = super::to_html(&[project]);
+
+        assert!(html.contains("<h2>Projet &lt;1&gt;</h2>"));
+        assert!(html.contains("<li class=\"completed\">Salut &amp; co</li>"));
+        assert!(html.contains("<li class=\"canceled\">Ciao</li>"));
+        assert!(html.contains("Tasks: 3"));
+        assert!(html.contains("Todo: 1"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::ops::Range;
+
+// Search stuff
+//
+// Case insensitive substring search over task titles. Ranges are byte ranges
+// into the original text so the UI can highlight them.
+
+pub fn find_matches(text: &str, search: &str) -> Vec<Range<usize>> {
+    let search: Vec<char> = search.chars().collect();
+    if search.is_empty() {
+        return vec![];
+    }
+
+    let mut matches = vec![];
+    let mut start_from = 0;
+    for (start, _) in text.char_indices() {
+        if start < start_from {
+            continue;
+        }
+        if let Some(end) = match_at(text, start, &search) {
+            matches.push(start..end);
+            start_from = end;
+        }
+    }
+    matches
+}
+
+pub fn is_match(text: &str, search: &str) -> bool {
+    !find_matches(text, search).is_empty()
+}
+
+// End of the match when `search` starts at byte `start` of `text`.
+fn match_at(text: &str, start: usize, search: &[char]) -> Option<usize> {
+    let mut chars = text[start..].char_indices();
+    for s in search {
+        let (_, c) = chars.next()?;
+        if !c.to_lowercase().eq(s.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(text.len(), |(i, _)| start + i))
+}
+
+// Tests
+
+#[cfg(test)]
+mod search_test {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        assert_eq!(find_matches("Buy milk", "MILK"), vec![4..8]);
+        assert_eq!(find_matches("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(find_matches("Écrire à Élise", "é"), vec![0..2, 11..13]);
+        assert!(find_matches("Buy milk", "").is_empty());
+        assert!(!is_match("Buy milk", "bread"));
+    }
+}
@@ -0,0 +1,48 @@
+use std::{env, fs, path::PathBuf, process};
+
+use crate::{app::App, comps::FileManager};
+
+// Test stuff
+//
+// Each test works on its own data file in the temp dir. `TempFiles` removes
+// it, with the history, archive and export files next to it, when dropped:
+// keep it alive until the end of the test.
+
+const EXTENSIONS: &[&str] = &["json", "history.json", "archive.json", "org", "report.html"];
+
+pub struct TempFiles {
+    pub path: PathBuf,
+}
+
+impl TempFiles {
+    // `name` must be unique among the tests and contain no `.`.
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("tuido-{}-{name}.json", process::id()));
+        let files = TempFiles { path };
+        files.remove();
+        files
+    }
+
+    pub fn get_file_manager(&self) -> FileManager {
+        FileManager::with_path(self.path.clone()).unwrap()
+    }
+
+    fn remove(&self) {
+        for extension in EXTENSIONS {
+            let _ = fs::remove_file(self.path.with_extension(extension));
+        }
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+// An app on a fresh data file, seeded with the welcome projects.
+pub fn build_app(name: &str) -> (App, TempFiles) {
+    let files = TempFiles::new(name);
+    let app = App::from_file_manager(files.get_file_manager()).unwrap();
+    (app, files)
+}
@@ -0,0 +1,400 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, str::FromStr};
+
+use crate::comps::TaskState;
+
+// Theme stuff
+//
+// A theme holds every glyph and style the UI draws with. Built-in themes can
+// be extended from the config file, see `ThemeConfig`.
+
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light", "high-contrast", "ascii"];
+pub const GLYPH_SETS: &[&str] = &["nerd", "unicode", "ascii"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyphs {
+    pub todo: String,
+    pub completed: String,
+    pub canceled: String,
+    pub previous_project: String,
+    pub next_project: String,
+    pub more_above: String,
+    pub more_below: String,
+    pub scroll_thumb: String,
+    pub scroll_track: String,
+    pub marked: String,
+}
+
+impl Glyphs {
+    pub fn from_name(name: &str) -> Option<Glyphs> {
+        let glyphs = match name {
+            "nerd" => [
+                "\u{f0130} ",
+                "\u{f05e0} ",
+                "\u{f0376} ",
+                "🞀",
+                "🞂",
+                "▲",
+                "▼",
+                "█",
+                "│",
+                "●",
+            ],
+            "unicode" => ["☐", "☑", "☒", "◀", "▶", "▲", "▼", "█", "│", "●"],
+            "ascii" => ["[ ]", "[x]", "[-]", "<", ">", "^", "v", "#", "|", "*"],
+            _ => return None,
+        };
+        Some(Glyphs {
+            todo: glyphs[0].to_string(),
+            completed: glyphs[1].to_string(),
+            canceled: glyphs[2].to_string(),
+            previous_project: glyphs[3].to_string(),
+            next_project: glyphs[4].to_string(),
+            more_above: glyphs[5].to_string(),
+            more_below: glyphs[6].to_string(),
+            scroll_thumb: glyphs[7].to_string(),
+            scroll_track: glyphs[8].to_string(),
+            marked: glyphs[9].to_string(),
+        })
+    }
+
+    pub fn get_checkbox(&self, state: &TaskState) -> &str {
+        match state {
+            TaskState::Todo => &self.todo,
+            TaskState::Completed => &self.completed,
+            TaskState::Canceled => &self.canceled,
+        }
+    }
+
+    fn get_glyph_mut(&mut self, name: &str) -> Option<&mut String> {
+        match name {
+            "todo" => Some(&mut self.todo),
+            "completed" => Some(&mut self.completed),
+            "canceled" => Some(&mut self.canceled),
+            "previous_project" => Some(&mut self.previous_project),
+            "next_project" => Some(&mut self.next_project),
+            "more_above" => Some(&mut self.more_above),
+            "more_below" => Some(&mut self.more_below),
+            "scroll_thumb" => Some(&mut self.scroll_thumb),
+            "scroll_track" => Some(&mut self.scroll_track),
+            "marked" => Some(&mut self.marked),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub glyphs: Glyphs,
+    pub border: Style,
+    pub todo: Style,
+    pub completed: Style,
+    pub canceled: Style,
+    pub todo_selected: Style,
+    pub completed_selected: Style,
+    pub canceled_selected: Style,
+    pub deleting: Style,
+    pub input: Style,
+    pub search_match: Style,
+    pub status: Style,
+    pub message: Style,
+    pub error: Style,
+    pub key: Style,
+    pub item_selected: Style,
+    pub current_project: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        let crossed_out = Style::default().add_modifier(Modifier::CROSSED_OUT);
+        Theme {
+            glyphs: Glyphs::from_name("nerd").unwrap(),
+            border: Style::default(),
+            todo: Style::default().fg(Color::White),
+            completed: Style::default().fg(Color::Green),
+            canceled: crossed_out.fg(Color::Gray),
+            todo_selected: Style::default().fg(Color::Black).bg(Color::LightBlue),
+            completed_selected: Style::default().fg(Color::Green).bg(Color::LightBlue),
+            canceled_selected: crossed_out.fg(Color::Gray).bg(Color::LightBlue),
+            deleting: Style::default().fg(Color::Black).bg(Color::Red),
+            input: Style::default().fg(Color::Yellow),
+            search_match: Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            status: Style::default().fg(Color::Gray),
+            message: Style::default().fg(Color::Cyan),
+            error: Style::default().fg(Color::Red),
+            key: Style::default().fg(Color::Yellow),
+            item_selected: Style::default().fg(Color::Black).bg(Color::LightBlue),
+            current_project: Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    pub fn light() -> Theme {
+        let crossed_out = Style::default().add_modifier(Modifier::CROSSED_OUT);
+        Theme {
+            todo: Style::default().fg(Color::Black),
+            completed: Style::default().fg(Color::Green),
+            canceled: crossed_out.fg(Color::DarkGray),
+            todo_selected: Style::default().fg(Color::White).bg(Color::Blue),
+            completed_selected: Style::default().fg(Color::LightGreen).bg(Color::Blue),
+            canceled_selected: crossed_out.fg(Color::Gray).bg(Color::Blue),
+            deleting: Style::default().fg(Color::White).bg(Color::Red),
+            input: Style::default().fg(Color::Magenta),
+            status: Style::default().fg(Color::DarkGray),
+            message: Style::default().fg(Color::Blue),
+            key: Style::default().fg(Color::Magenta),
+            item_selected: Style::default().fg(Color::White).bg(Color::Blue),
+            current_project: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            ..Theme::dark()
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let selected = bold.fg(Color::Black).bg(Color::White);
+        Theme {
+            todo: bold.fg(Color::White),
+            completed: bold.fg(Color::LightGreen),
+            canceled: bold.fg(Color::Gray).add_modifier(Modifier::CROSSED_OUT),
+            todo_selected: selected,
+            completed_selected: selected,
+            canceled_selected: selected.add_modifier(Modifier::CROSSED_OUT),
+            deleting: bold.fg(Color::White).bg(Color::Red),
+            input: bold.fg(Color::LightYellow),
+            status: Style::default().fg(Color::White),
+            message: bold.fg(Color::LightCyan),
+            error: bold.fg(Color::LightRed),
+            key: bold.fg(Color::LightYellow),
+            item_selected: selected,
+            current_project: bold.fg(Color::White).add_modifier(Modifier::UNDERLINED),
+            ..Theme::dark()
+        }
+    }
+
+    pub fn ascii() -> Theme {
+        Theme {
+            glyphs: Glyphs::from_name("ascii").unwrap(),
+            ..Theme::dark()
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "ascii" => Some(Theme::ascii()),
+            _ => None,
+        }
+    }
+
+    // Picks `name` among the user themes first, then the built-in ones.
+    pub fn build(
+        name: Option<&str>,
+        themes: &HashMap<String, ThemeConfig>,
+    ) -> Result<Theme, Box<dyn Error>> {
+        let name = name.unwrap_or("dark");
+        match themes.get(name) {
+            Some(config) => config
+                .to_theme()
+                .map_err(|e| format!("theme '{name}': {e}").into()),
+            None => Theme::from_name(name).ok_or_else(|| {
+                format!(
+                    "unknown theme '{name}' (expected {} or a [themes.{name}] section)",
+                    BUILTIN_THEMES.join(", ")
+                )
+                .into()
+            }),
+        }
+    }
+
+    pub fn get_task_style(&self, state: &TaskState, selected: bool) -> Style {
+        match (state, selected) {
+            (TaskState::Todo, false) => self.todo,
+            (TaskState::Completed, false) => self.completed,
+            (TaskState::Canceled, false) => self.canceled,
+            (TaskState::Todo, true) => self.todo_selected,
+            (TaskState::Completed, true) => self.completed_selected,
+            (TaskState::Canceled, true) => self.canceled_selected,
+        }
+    }
+
+    fn get_style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        match name {
+            "border" => Some(&mut self.border),
+            "todo" => Some(&mut self.todo),
+            "completed" => Some(&mut self.completed),
+            "canceled" => Some(&mut self.canceled),
+            "todo_selected" => Some(&mut self.todo_selected),
+            "completed_selected" => Some(&mut self.completed_selected),
+            "canceled_selected" => Some(&mut self.canceled_selected),
+            "deleting" => Some(&mut self.deleting),
+            "input" => Some(&mut self.input),
+            "search_match" => Some(&mut self.search_match),
+            "status" => Some(&mut self.status),
+            "message" => Some(&mut self.message),
+            "error" => Some(&mut self.error),
+            "key" => Some(&mut self.key),
+            "item_selected" => Some(&mut self.item_selected),
+            "current_project" => Some(&mut self.current_project),
+            _ => None,
+        }
+    }
+}
+
+// A user theme: a built-in base, an optional glyph set, then single glyphs
+// and styles replaced by name.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub glyph_set: Option<String>,
+    pub glyphs: HashMap<String, String>,
+    pub styles: HashMap<String, String>,
+}
+
+impl ThemeConfig {
+    pub fn to_theme(&self) -> Result<Theme, Box<dyn Error>> {
+        let base = self.base.as_deref().unwrap_or("dark");
+        let mut theme = Theme::from_name(base).ok_or_else(|| {
+            format!(
+                "unknown base theme '{base}' (expected {})",
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+
+        if let Some(glyph_set) = &self.glyph_set {
+            theme.glyphs = Glyphs::from_name(glyph_set).ok_or_else(|| {
+                format!(
+                    "unknown glyph set '{glyph_set}' (expected {})",
+                    GLYPH_SETS.join(", ")
+                )
+            })?;
+        }
+        for (name, glyph) in &self.glyphs {
+            *theme
+                .glyphs
+                .get_glyph_mut(name)
+                .ok_or_else(|| format!("unknown glyph '{name}'"))? = glyph.clone();
+        }
+        for (name, spec) in &self.styles {
+            *theme
+                .get_style_mut(name)
+                .ok_or_else(|| format!("unknown style '{name}'"))? = parse_style(spec)?;
+        }
+        Ok(theme)
+    }
+}
+
+// Parses "yellow", "black on lightblue bold" or "#ff8800 on 236 crossed_out".
+pub fn parse_style(spec: &str) -> Result<Style, Box<dyn Error>> {
+    let mut style = Style::default();
+    let mut words = spec.split_whitespace();
+    let mut has_fg = false;
+
+    while let Some(word) = words.next() {
+        let modifier = match word.to_lowercase().as_str() {
+            "bold" => Some(Modifier::BOLD),
+            "dim" => Some(Modifier::DIM),
+            "italic" => Some(Modifier::ITALIC),
+            "underlined" => Some(Modifier::UNDERLINED),
+            "reversed" => Some(Modifier::REVERSED),
+            "crossed_out" => Some(Modifier::CROSSED_OUT),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            style = style.add_modifier(modifier);
+        } else if word == "on" {
+            let color = words
+                .next()
+                .ok_or_else(|| format!("missing color after 'on' in '{spec}'"))?;
+            style = style.bg(parse_color(color)?);
+        } else if !has_fg {
+            style = style.fg(parse_color(word)?);
+            has_fg = true;
+        } else {
+            return Err(format!("unexpected '{word}' in style '{spec}'").into());
+        }
+    }
+    Ok(style)
+}
+
+fn parse_color(name: &str) -> Result<Color, Box<dyn Error>> {
+    if name == "default" {
+        return Ok(Color::Reset);
+    }
+    Color::from_str(name).map_err(|_| format!("unknown color '{name}'").into())
+}
+
+// Tests
+
+#[cfg(test)]
+mod theme_test {
+    use super::*;
+
+    #[test]
+    fn builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::from_name(name).is_some());
+        }
+        assert!(Theme::ascii().glyphs.todo.is_ascii());
+        assert!(Theme::build(Some("neon"), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn styles() {
+        assert_eq!(
+            parse_style("black on lightblue bold").unwrap(),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            parse_style("#ff8800").unwrap(),
+            Style::default().fg(Color::Rgb(255, 136, 0))
+        );
+        assert!(parse_style("red green").is_err());
+        assert!(parse_style("black on").is_err());
+        assert!(parse_style("purplish").is_err());
+    }
+
+    #[test]
+    fn user_theme() {
+        let config = ThemeConfig {
+            base: Some(String::from("light")),
+            glyph_set: Some(String::from("unicode")),
+            glyphs: HashMap::from([(String::from("todo"), String::from("o"))]),
+            styles: HashMap::from([(String::from("todo"), String::from("red"))]),
+        };
+        let theme = Theme::build(
+            Some("mine"),
+            &HashMap::from([(String::from("mine"), config)]),
+        )
+        .unwrap();
+
+        assert_eq!(theme.glyphs.todo, "o");
+        assert_eq!(theme.glyphs.completed, "☑");
+        assert_eq!(theme.todo, Style::default().fg(Color::Red));
+        assert_eq!(theme.completed, Theme::light().completed);
+
+        let config = ThemeConfig {
+            styles: HashMap::from([(String::from("tod"), String::from("red"))]),
+            ..ThemeConfig::default()
+        };
+        assert!(config.to_theme().is_err());
+    }
+}
@@ -1,20 +1,31 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Position},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState,
+    },
     Frame,
 };
 
+const SIDEBAR_WIDTH: u16 = 28;
+// Below this width the sidebar only shows up, over the tasks, while focused.
+const SIDEBAR_MIN_WIDTH: u16 = 70;
+
 use crate::{
     app::{App, ScreenMode},
-    comps::TaskState,
+    comps::{Task, TaskState},
+    date,
+    mouse::{HitArea, Target},
+    palette, search,
+    theme::Theme,
 };
 
-struct Stats {
-    tasks: u32,
-    completed: u32,
-    canceled: u32,
+pub struct Stats {
+    pub tasks: u32,
+    pub completed: u32,
+    pub canceled: u32,
 }
 
 impl Stats {
@@ -35,6 +46,10 @@ impl Stats {
         }
     }
 
+    pub fn todo(&self) -> u32 {
+        self.tasks - self.completed - self.canceled
+    }
+
     pub fn get_string(&self) -> String {
         format!(
             " Tasks: {} - Completed: {} - Canceled: {} ",
@@ -43,12 +58,39 @@ impl Stats {
     }
 }
 
-pub fn ui(f: &mut Frame, app: &App) {
+pub fn ui(f: &mut Frame, app: &mut App) {
+    let show_status = matches!(
+        app.screen_mode,
+        ScreenMode::Filtering
+            | ScreenMode::Searching
+            | ScreenMode::MovingTasks
+            | ScreenMode::TaggingTasks
+            | ScreenMode::ConfirmingBatch
+    ) || app.filter.is_some()
+        || !app.get_marked_ids().is_empty()
+        || app.message.is_some()
+        || app.is_vi_normal();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(1)])
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(if show_status { 1 } else { 0 }),
+        ])
         .split(f.area());
 
+    app.hit_areas.clear();
+    let docked = app.show_sidebar && chunks[0].width >= SIDEBAR_MIN_WIDTH;
+    let (sidebar_area, list_area) = if docked {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(SIDEBAR_WIDTH), Constraint::Min(1)])
+            .split(chunks[0]);
+        (panes[0], panes[1])
+    } else {
+        let width = SIDEBAR_WIDTH.min(chunks[0].width);
+        (Rect { width, ..chunks[0] }, chunks[0])
+    };
+
     // Affichage projet en cours
 
     //let project_block = Block::default()
@@ -66,87 +108,114 @@ pub fn ui(f: &mut Frame, app: &App) {
 
     // Affichage des tâches
 
-    let mut list_items = Vec::<ListItem>::new();
-    let mut task_count: u16 = 0;
-
-    let mut stats = Stats::new();
-
-    if let Some(tasks) = app.get_current_project_tasks() {
-        for task in tasks {
-            task_count += 1;
-
-            let fmt;
-            let style;
-            if task.id == app.current_task_id {
-                match app.screen_mode {
-                    ScreenMode::Main | ScreenMode::AddingTask | ScreenMode::RenamingProject => {
-                        fmt = format!(" {} - {}", get_checkbox(&task.state), task.title);
-                        style = get_style_selected(&task.state);
-                    }
-                    ScreenMode::DeletingTask => {
-                        let text = format!("{}  [Delete Task? (y/n)]", task.title);
-                        fmt = format!(" {} - {}", get_checkbox(&task.state), text);
-                        style = Style::default().fg(Color::Black).bg(Color::Red);
-                    }
-                    ScreenMode::RenamingTask => {
-                        fmt = format!(
-                            " {} - {}",
-                            get_checkbox(&task.state),
-                            app.cursor_manager.string
-                        );
-                        style = Style::default().fg(Color::Yellow);
-                        f.set_cursor_position(Position::new(
-                            7 + app.cursor_manager.cursor_position,
-                            task_count,
-                        ));
-                    }
+    app.hit_areas.push(HitArea {
+        area: list_area,
+        target: Target::Tasks,
+    });
+    if app.screen_mode == ScreenMode::Archive {
+        render_archive(f, app, list_area);
+    } else if app.board {
+        render_board(f, app, list_area);
+    } else {
+        render_tasks(f, app, list_area);
+    }
+    add_arrow_hit_areas(app, list_area);
+
+    if app.screen_mode == ScreenMode::RenamingProject {
+        let arrow_width = Span::raw(&app.theme.glyphs.previous_project).width() as u16;
+        f.set_cursor_position(Position::new(
+            list_area.x + 3 + arrow_width + app.cursor_manager.get_display_position(),
+            list_area.y,
+        ));
+    }
+    let theme = &app.theme;
+
+    // Affichage du filtre et des messages
+
+    if show_status {
+        let mut status_line = match app.screen_mode {
+            ScreenMode::Filtering => {
+                f.set_cursor_position(Position::new(
+                    chunks[1].x + 9 + app.cursor_manager.get_display_position(),
+                    chunks[1].y,
+                ));
+                let mut spans = vec![Span::styled(
+                    format!(" Filter: {}", app.cursor_manager.string),
+                    theme.input,
+                )];
+                if let Some(error) = &app.filter_error {
+                    spans.push(Span::styled(format!("  [{error}]"), theme.error));
                 }
-            } else {
-                fmt = format!(" {} - {}", get_checkbox(&task.state), task.title);
-                style = get_style(&task.state);
+                Line::from(spans)
             }
-
-            stats.add_task(&task.state);
-
-            list_items.push(ListItem::new(Line::from(Span::styled(fmt, style))));
+            ScreenMode::Searching => {
+                f.set_cursor_position(Position::new(
+                    chunks[1].x + 9 + app.cursor_manager.get_display_position(),
+                    chunks[1].y,
+                ));
+                Line::from(Span::styled(
+                    format!(" Search: {}", app.cursor_manager.string),
+                    theme.input,
+                ))
+            }
+            ScreenMode::MovingTasks | ScreenMode::TaggingTasks => {
+                let label = match app.screen_mode {
+                    ScreenMode::MovingTasks => " Move to project: ",
+                    _ => " Tag with: ",
+                };
+                f.set_cursor_position(Position::new(
+                    chunks[1].x + label.len() as u16 + app.cursor_manager.get_display_position(),
+                    chunks[1].y,
+                ));
+                Line::from(Span::styled(
+                    format!("{label}{}", app.cursor_manager.string),
+                    theme.input,
+                ))
+            }
+            ScreenMode::ConfirmingBatch => {
+                let question = app
+                    .batch
+                    .as_ref()
+                    .map(|batch| batch.get_question(&app.projects))
+                    .unwrap_or_default();
+                Line::from(Span::styled(format!(" {question} (y/n)"), theme.deleting))
+            }
+            _ if app.filter.is_some() => Line::from(Span::styled(
+                format!(" Filter: {}", app.filter_string),
+                theme.status,
+            )),
+            _ => Line::default(),
+        };
+        let marked = app.get_marked_ids().len();
+        if marked > 0 && app.screen_mode != ScreenMode::ConfirmingBatch {
+            status_line.push_span(Span::styled(format!(" {marked} marked"), theme.status));
         }
-    }
-
-    // Ajout d'une tâche
-
-    match app.screen_mode {
-        ScreenMode::AddingTask => {
-            task_count += 1;
-            list_items.push(ListItem::new(Line::from(Span::styled(
-                format!(" 󰄰  - {}", app.cursor_manager.string),
-                Style::default().fg(Color::Yellow),
-            ))));
-            f.set_cursor_position(Position::new(
-                7 + app.cursor_manager.cursor_position,
-                task_count,
-            ));
+        if app.is_vi_normal() {
+            status_line.push_span(Span::styled(" -- NORMAL --", theme.status));
         }
-        ScreenMode::RenamingProject => {
-            f.set_cursor_position(Position::new(4 + app.cursor_manager.cursor_position, 0));
+        if let Some(message) = &app.message {
+            status_line.push_span(Span::styled(format!(" {message}"), theme.message));
         }
-        _ => {}
+        f.render_widget(Paragraph::new(status_line), chunks[1]);
     }
 
-    let project_name;
-    match app.screen_mode {
-        ScreenMode::RenamingProject => project_name = app.cursor_manager.string.clone(),
-        _ => project_name = app.get_current_project_name(),
+    // Affichage des projets
+
+    if docked || app.screen_mode == ScreenMode::Sidebar {
+        render_sidebar(f, app, sidebar_area);
     }
 
-    let tasks_block = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default())
-        .title(format!(" 🞀 {} 🞂 ", project_name))
-        .title_bottom(stats.get_string());
+    // Affichage de la palette
 
-    let list = List::new(list_items).block(tasks_block);
+    if app.screen_mode == ScreenMode::Palette {
+        render_palette(f, app);
+    }
 
-    f.render_widget(list, chunks[0]);
+    // Affichage de l'aide
+
+    if app.show_help {
+        render_help(f, app);
+    }
 
     // Affichage du footer
 
@@ -178,32 +247,717 @@ pub fn ui(f: &mut Frame, app: &App) {
     //f.render_widget(key_notes_footer, footer_chunks[1]);
 }
 
-fn get_checkbox(state: &TaskState) -> String {
-    match state {
-        TaskState::Todo => "󰄰 ",
-        TaskState::Canceled => "󰍶 ",
-        TaskState::Completed => "󰗠 ",
+fn render_tasks(f: &mut Frame, app: &mut App, area: Rect) {
+    let mut list_items = Vec::<ListItem>::new();
+    let mut rows = vec![];
+    let mut selected = None;
+    let mut cursor_x = None;
+
+    let mut stats = Stats::new();
+    let theme = &app.theme;
+
+    if let Some(project) = app.projects.iter().find(|p| p.id == app.current_project_id) {
+        for task in &project.tasks {
+            stats.add_task(&task.state);
+        }
+        let tasks: Vec<&Task> = project
+            .get_sorted_tasks()
+            .into_iter()
+            .filter(|task| app.is_task_visible(project, task))
+            .collect();
+
+        for (i, task) in tasks.iter().enumerate() {
+            // En-tête de section
+            if project.view.grouped
+                && tasks
+                    .get(i.wrapping_sub(1))
+                    .is_none_or(|t| t.state != task.state)
+            {
+                let count = tasks.iter().filter(|t| t.state == task.state).count();
+                rows.push(None);
+                list_items.push(ListItem::new(Line::from(Span::styled(
+                    format!(" {} ({count})", task.state.get_name()),
+                    theme.key,
+                ))));
+            }
+
+            let line = if task.id == app.current_task_id {
+                selected = Some(list_items.len());
+                if app.screen_mode == ScreenMode::RenamingTask {
+                    cursor_x = Some(get_prefix_width(theme, &task.state));
+                }
+                get_selected_task_line(app, task)
+            } else {
+                get_task_line(
+                    &task.state,
+                    &task.title,
+                    &app.search,
+                    theme,
+                    false,
+                    app.is_marked(task.id),
+                )
+            };
+
+            let mut line = line;
+            if app.show_dates {
+                line.push_span(Span::styled(
+                    format!("  {}", date::format_date(get_shown_date(task))),
+                    theme.status,
+                ));
+            }
+            rows.push(Some((task.id, get_prefix_width(theme, &task.state))));
+            list_items.push(ListItem::new(line));
+        }
+    }
+
+    // Ajout d'une tâche
+
+    if app.screen_mode == ScreenMode::AddingTask {
+        let prefix = get_task_prefix(theme, &TaskState::Todo);
+        selected = Some(list_items.len());
+        cursor_x = Some(Span::raw(&prefix).width() as u16);
+        list_items.push(ListItem::new(Line::from(Span::styled(
+            format!("{prefix}{}", app.cursor_manager.string),
+            theme.input,
+        ))));
+    }
+
+    // Défilement : the offset only moves when the selection leaves the view.
+
+    let height = area.height.saturating_sub(2) as usize;
+    let total = list_items.len();
+    let mut offset = app.task_list_state.offset();
+    if let Some(index) = selected {
+        if index < offset {
+            offset = index;
+        } else if index >= offset + height {
+            offset = index + 1 - height;
+        }
+    }
+    offset = offset.min(total.saturating_sub(height));
+    let above = offset;
+    let below = total.saturating_sub(offset + height);
+
+    let mut tasks_block = get_tasks_block(app, &stats);
+
+    if above > 0 {
+        tasks_block = tasks_block.title(
+            Line::from(format!(" {} {above} more ", theme.glyphs.more_above))
+                .style(theme.status)
+                .centered(),
+        );
+    }
+    if below > 0 {
+        tasks_block = tasks_block.title_bottom(
+            Line::from(format!(" {} {below} more ", theme.glyphs.more_below))
+                .style(theme.status)
+                .centered(),
+        );
+    }
+
+    if let (Some(index), Some(x)) = (selected, cursor_x) {
+        f.set_cursor_position(Position::new(
+            area.x + 1 + x + app.cursor_manager.get_display_position(),
+            area.y + 1 + (index - offset) as u16,
+        ));
+    }
+
+    let list = List::new(list_items).block(tasks_block);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .thumb_symbol(&theme.glyphs.scroll_thumb)
+        .track_symbol(Some(&theme.glyphs.scroll_track))
+        .style(theme.status);
+    let mut scrollbar_state = ScrollbarState::new(total.saturating_sub(height)).position(offset);
+
+    add_task_hit_areas(
+        &mut app.hit_areas,
+        area.inner(Margin::new(1, 1)),
+        &rows,
+        offset,
+    );
+    app.page_size = height.max(1);
+    *app.task_list_state.offset_mut() = offset;
+    app.task_list_state.select(selected);
+    f.render_stateful_widget(list, area, &mut app.task_list_state);
+    if total > height {
+        f.render_stateful_widget(
+            scrollbar,
+            area.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+    }
+}
+
+fn render_board(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let mut stats = Stats::new();
+    let mut columns: Vec<Vec<&Task>> = vec![vec![]; TaskState::ALL.len()];
+    if let Some(project) = app.projects.iter().find(|p| p.id == app.current_project_id) {
+        for task in &project.tasks {
+            stats.add_task(&task.state);
+        }
+        for task in project.get_sorted_tasks() {
+            if app.is_task_visible(project, task) {
+                let column = TaskState::ALL.iter().position(|s| *s == task.state);
+                columns[column.unwrap()].push(task);
+            }
+        }
+    }
+
+    let board_block = get_tasks_block(app, &stats);
+    let inner = board_block.inner(area);
+    f.render_widget(board_block, area);
+    let column_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, TaskState::ALL.len() as u32);
+            TaskState::ALL.len()
+        ])
+        .split(inner);
+
+    let mut cursor = None;
+    for ((state, tasks), column_area) in
+        TaskState::ALL.iter().zip(&columns).zip(column_areas.iter())
+    {
+        let mut items = vec![];
+        let mut rows = vec![];
+        let mut selected = None;
+        let mut cursor_x = None;
+        for task in tasks {
+            rows.push(Some((task.id, get_prefix_width(theme, state))));
+            if task.id == app.current_task_id {
+                selected = Some(items.len());
+                if app.screen_mode == ScreenMode::RenamingTask {
+                    cursor_x = Some(get_prefix_width(theme, state));
+                }
+                items.push(ListItem::new(get_selected_task_line(app, task)));
+            } else {
+                items.push(ListItem::new(get_task_line(
+                    state,
+                    &task.title,
+                    &app.search,
+                    theme,
+                    false,
+                    app.is_marked(task.id),
+                )));
+            }
+        }
+        if app.screen_mode == ScreenMode::AddingTask && *state == TaskState::Todo {
+            let prefix = get_task_prefix(theme, state);
+            selected = Some(items.len());
+            cursor_x = Some(Span::raw(&prefix).width() as u16);
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("{prefix}{}", app.cursor_manager.string),
+                theme.input,
+            ))));
+        }
+
+        let column_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(if selected.is_some() {
+                theme.key
+            } else {
+                theme.border
+            })
+            .title(format!(" {} ({}) ", state.get_name(), tasks.len()));
+        let mut list_state = ListState::default().with_selected(selected);
+        f.render_stateful_widget(
+            List::new(items).block(column_block),
+            *column_area,
+            &mut list_state,
+        );
+        add_task_hit_areas(
+            &mut app.hit_areas,
+            column_area.inner(Margin::new(1, 1)),
+            &rows,
+            list_state.offset(),
+        );
+        if let (Some(index), Some(x)) = (selected, cursor_x) {
+            cursor = Some(Position::new(
+                column_area.x + 1 + x + app.cursor_manager.get_display_position(),
+                column_area.y + 1 + (index - list_state.offset()) as u16,
+            ));
+        }
+    }
+
+    if let Some(position) = cursor {
+        f.set_cursor_position(position);
+    }
+    app.page_size = inner.height.saturating_sub(2).max(1) as usize;
+}
+
+// Archived tasks of the current project, latest first, with the date they
+// were finished.
+fn render_archive(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let entries = app.get_archive_entries();
+    let selected = app.archive_index.min(entries.len().saturating_sub(1));
+
+    let mut items = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let task = &entry.task;
+        let date = match task.completed_at {
+            Some(timestamp) => date::format_date(timestamp),
+            None => String::from("          "),
+        };
+        let mut line = get_task_line(&task.state, &task.title, "", theme, i == selected, false);
+        line.spans
+            .insert(0, Span::styled(format!(" {date}"), theme.status));
+        items.push(ListItem::new(line));
+    }
+    if items.is_empty() {
+        items.push(ListItem::new(Span::styled(
+            " No archived task in this project",
+            theme.status,
+        )));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .title(format!(
+            " {} {} {} ",
+            theme.glyphs.previous_project,
+            app.get_current_project_name(),
+            theme.glyphs.next_project
+        ))
+        .title(Line::from(" Archive ").right_aligned())
+        .title_bottom(format!(" Archived tasks: {} ", entries.len()));
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(List::new(items).block(block), area, &mut list_state);
+}
+
+// Outer block of the task list and the board: project name, search matches
+// and statistics.
+fn get_tasks_block(app: &App, stats: &Stats) -> Block<'static> {
+    let theme = &app.theme;
+    let project_name = match app.screen_mode {
+        ScreenMode::RenamingProject => app.cursor_manager.string.clone(),
+        _ => app.get_current_project_name(),
+    };
+
+    let mut tasks_block = Block::default()
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .title(format!(
+            " {} {} {} ",
+            theme.glyphs.previous_project, project_name, theme.glyphs.next_project
+        ))
+        .title_bottom(stats.get_string());
+
+    let view = match app.projects.iter().find(|p| p.id == app.current_project_id) {
+        Some(project) => {
+            let hidden = app.get_hide_mode(project).get_name();
+            let mut parts = vec![project.view.get_description()];
+            if !hidden.is_empty() {
+                parts.push(format!("{hidden} hidden"));
+            }
+            parts.retain(|p| !p.is_empty());
+            parts.join(", ")
+        }
+        None => String::new(),
+    };
+    if !view.is_empty() {
+        tasks_block = tasks_block.title_bottom(Line::from(format!(" {view} ")).right_aligned());
+    }
+
+    if !app.search.is_empty() {
+        let matches = app.get_search_matches();
+        let current = (app.current_project_id, app.current_task_id);
+        let index = match matches.iter().position(|m| *m == current) {
+            Some(i) => (i + 1).to_string(),
+            None => String::from("-"),
+        };
+        tasks_block = tasks_block.title(
+            Line::from(format!(" /{} [{}/{}] ", app.search, index, matches.len())).right_aligned(),
+        );
+    }
+    tasks_block
+}
+
+fn get_selected_task_line<'a>(app: &App, task: &'a Task) -> Line<'a> {
+    let theme = &app.theme;
+    match app.screen_mode {
+        ScreenMode::DeletingTask => {
+            let text = format!("{}  [Delete Task? (y/n)]", task.title);
+            Line::from(Span::styled(
+                format!("{}{}", get_task_prefix(theme, &task.state), text),
+                theme.deleting,
+            ))
+        }
+        ScreenMode::RenamingTask => Line::from(Span::styled(
+            format!(
+                "{}{}",
+                get_task_prefix(theme, &task.state),
+                app.cursor_manager.string
+            ),
+            theme.input,
+        )),
+        _ => get_task_line(
+            &task.state,
+            &task.title,
+            &app.search,
+            theme,
+            true,
+            app.is_marked(task.id),
+        ),
+    }
+}
+
+fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let focused = app.screen_mode == ScreenMode::Sidebar;
+    let mut projects: Vec<_> = app.projects.iter().collect();
+    projects.sort_by_key(|p| p.id);
+
+    // Name on the left, open/done counts on the right.
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = projects
+        .iter()
+        .map(|project| {
+            let mut stats = Stats::new();
+            for task in &project.tasks {
+                stats.add_task(&task.state);
+            }
+            let counts = format!("{}/{} ", stats.todo(), stats.completed);
+            let name_width = width.saturating_sub(counts.len() + 1);
+            let name: String = project.name.chars().take(name_width).collect();
+            let style = match (project.id == app.current_project_id, focused) {
+                (true, true) => theme.item_selected,
+                (true, false) => theme.current_project,
+                _ => theme.todo,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {name:<name_width$}"), style),
+                Span::styled(counts, style.patch(theme.status)),
+            ]))
+        })
+        .collect();
+
+    let sidebar_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(if focused { theme.key } else { theme.border })
+        .title(" Projects ")
+        .title_bottom(Line::from(" open/done ").right_aligned());
+    let selected = projects.iter().position(|p| p.id == app.current_project_id);
+
+    let ids: Vec<u32> = projects.iter().map(|p| p.id).collect();
+
+    f.render_widget(Clear, area);
+    app.project_list_state.select(selected);
+    f.render_stateful_widget(
+        List::new(items).block(sidebar_block),
+        area,
+        &mut app.project_list_state,
+    );
+
+    app.hit_areas.push(HitArea {
+        area,
+        target: Target::Sidebar,
+    });
+    let inner = area.inner(Margin::new(1, 1));
+    for (row, id) in ids
+        .iter()
+        .skip(app.project_list_state.offset())
+        .take(inner.height as usize)
+        .enumerate()
+    {
+        app.hit_areas.push(HitArea {
+            area: Rect::new(inner.x, inner.y + row as u16, inner.width, 1),
+            target: Target::Project(*id),
+        });
     }
-    .to_string()
 }
 
-fn get_style(state: &TaskState) -> Style {
-    match state {
-        TaskState::Todo => Style::default().fg(Color::White),
-        TaskState::Canceled => Style::default()
-            .fg(Color::Gray)
-            .add_modifier(Modifier::CROSSED_OUT),
-        TaskState::Completed => Style::default().fg(Color::Green),
+// One area per visible task row, plus one over its checkbox. Section
+// headers are `None` rows.
+fn add_task_hit_areas(
+    hit_areas: &mut Vec<HitArea>,
+    area: Rect,
+    rows: &[Option<(u32, u16)>],
+    offset: usize,
+) {
+    for (row, task_row) in rows
+        .iter()
+        .skip(offset)
+        .take(area.height as usize)
+        .enumerate()
+    {
+        let Some((id, prefix_width)) = task_row else {
+            continue;
+        };
+        let row_area = Rect::new(area.x, area.y + row as u16, area.width, 1);
+        hit_areas.push(HitArea {
+            area: row_area,
+            target: Target::Task(*id),
+        });
+        hit_areas.push(HitArea {
+            area: Rect {
+                width: (*prefix_width).min(area.width),
+                ..row_area
+            },
+            target: Target::Checkbox(*id),
+        });
     }
 }
 
-fn get_style_selected(state: &TaskState) -> Style {
-    match state {
-        TaskState::Todo => Style::default().fg(Color::Black).bg(Color::LightBlue),
-        TaskState::Canceled => Style::default()
-            .fg(Color::Gray)
-            .add_modifier(Modifier::CROSSED_OUT)
-            .bg(Color::LightBlue),
-        TaskState::Completed => Style::default().fg(Color::Green).bg(Color::LightBlue),
+// The arrows around the project name in the title of `area`.
+fn add_arrow_hit_areas(app: &mut App, area: Rect) {
+    let glyphs = &app.theme.glyphs;
+    let previous_width = Span::raw(&glyphs.previous_project).width() as u16;
+    let next_width = Span::raw(&glyphs.next_project).width() as u16;
+    let name_width = Span::raw(app.get_current_project_name()).width() as u16;
+    let previous_x = area.x + 2;
+    let next_x = previous_x + previous_width + 1 + name_width + 1;
+    let arrows = [
+        (previous_x, previous_width, Target::PreviousProject),
+        (next_x, next_width, Target::NextProject),
+    ];
+    for (x, width, target) in arrows {
+        let area = Rect::new(x, area.y, width, 1).intersection(area);
+        if !area.is_empty() {
+            app.hit_areas.push(HitArea { area, target });
+        }
+    }
+}
+
+fn render_palette(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let matches = palette::get_matches(app, &app.cursor_manager.string);
+    let area = f.area();
+    let width = (area.width * 3 / 5).max(40);
+    let height = (matches.len() as u16 + 5).clamp(6, 16);
+    let mut popup = get_centered_rect(area, width, height);
+    popup.y = area.y + area.height / 6;
+    popup.height = popup.height.min(area.bottom().saturating_sub(popup.y));
+
+    let palette_block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Command palette ")
+        .title_bottom(format!(" {} match(es) ", matches.len()));
+    let inner = palette_block.inner(popup);
+    f.render_widget(Clear, popup);
+    f.render_widget(palette_block, popup);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(" > ", theme.key),
+            Span::raw(app.cursor_manager.string.clone()),
+        ])),
+        rows[0],
+    );
+    f.set_cursor_position(Position::new(
+        rows[0].x + 3 + app.cursor_manager.get_display_position(),
+        rows[0].y,
+    ));
+
+    // Keep the selected entry in view when the list is taller than the popup.
+    let visible = rows[1].height as usize;
+    let offset = (app.palette_index + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = matches
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, entry)| {
+            let style = if i == app.palette_index {
+                theme.item_selected
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(Span::styled(format!(" {}", entry.label), style)))
+        })
+        .collect();
+    f.render_widget(List::new(items), rows[1]);
+
+    if let Some(entry) = matches.get(app.palette_index) {
+        f.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!(" {}", entry.preview),
+                theme.status,
+            ))),
+            rows[2],
+        );
+    }
+}
+
+fn render_help(f: &mut Frame, app: &mut App) {
+    let help = app.keymap.get_help(&app.screen_mode);
+    let keys_width = help.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+    let lines: Vec<Line> = help
+        .iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(format!(" {keys:>keys_width$} "), app.theme.key),
+                Span::raw(format!(" {description}")),
+            ])
+        })
+        .collect();
+
+    let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 3;
+    let area = get_centered_rect(f.area(), width, lines.len() as u16 + 2);
+
+    // Scrolls when the terminal is too short for every binding.
+    let total = lines.len();
+    let height = area.height.saturating_sub(2) as usize;
+    app.help_offset = app.help_offset.min(total.saturating_sub(height));
+    let above = app.help_offset;
+    let below = total.saturating_sub(above + height);
+
+    let theme = &app.theme;
+    let mut help_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Help - {} ", app.screen_mode.get_name()));
+    if above > 0 {
+        help_block = help_block.title(
+            Line::from(format!(" {} {above} more ", theme.glyphs.more_above))
+                .style(theme.status)
+                .right_aligned(),
+        );
+    }
+    help_block = if above + below > 0 {
+        help_block.title_bottom(" Up/Down to scroll ")
+    } else {
+        help_block.title_bottom(" Press any key to close ")
+    };
+    if below > 0 {
+        help_block = help_block.title_bottom(
+            Line::from(format!(" {} {below} more ", theme.glyphs.more_below))
+                .style(theme.status)
+                .right_aligned(),
+        );
+    }
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(help_block)
+            .scroll((above as u16, 0)),
+        area,
+    );
+}
+
+fn get_centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+// Open tasks show when they were created, finished ones when they were
+// finished.
+fn get_shown_date(task: &Task) -> u64 {
+    task.completed_at.unwrap_or(task.created_at)
+}
+
+// Width of the clickable checkbox part of a row.
+fn get_prefix_width(theme: &Theme, state: &TaskState) -> u16 {
+    Span::raw(get_task_prefix(theme, state)).width() as u16
+}
+
+fn get_task_prefix(theme: &Theme, state: &TaskState) -> String {
+    format!(" {} - ", theme.glyphs.get_checkbox(state))
+}
+
+// Task row with the parts of the title matching `search` highlighted. The
+// mark of a marked task replaces the leading space.
+fn get_task_line<'a>(
+    state: &TaskState,
+    title: &'a str,
+    search: &str,
+    theme: &Theme,
+    selected: bool,
+    marked: bool,
+) -> Line<'a> {
+    let style = theme.get_task_style(state, selected);
+    let highlight = style.patch(theme.search_match);
+    let prefix = get_task_prefix(theme, state);
+    let mut spans = if marked {
+        vec![
+            Span::styled(theme.glyphs.marked.clone(), style.patch(theme.key)),
+            Span::styled(prefix[1..].to_string(), style),
+        ]
+    } else {
+        vec![Span::styled(prefix, style)]
+    };
+    let mut position = 0;
+    for range in search::find_matches(title, search) {
+        spans.push(Span::styled(&title[position..range.start], style));
+        spans.push(Span::styled(&title[range.clone()], highlight));
+        position = range.end;
+    }
+    spans.push(Span::styled(&title[position..], style));
+    Line::from(spans)
+}
+
+// Tests
+
+#[cfg(test)]
+mod ui_test {
+    use super::*;
+    use crate::{action::Action, testing::build_app};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn scroll_follows_selection() {
+        let (mut app, _files) = build_app("ui_scroll");
+        for i in 0..30 {
+            app.dispatch(Action::CreateTask(format!("Task {i}")))
+                .unwrap();
+        }
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+
+        app.dispatch(Action::FirstTask).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.task_list_state.offset(), 0);
+        assert_eq!(app.page_size, 8);
+
+        app.dispatch(Action::LastTask).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.task_list_state.offset(), 24);
+
+        // Moving up inside the view keeps the offset.
+        app.dispatch(Action::PreviousTask).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.task_list_state.offset(), 24);
+    }
+
+    #[test]
+    fn help_scrolls() {
+        let (mut app, _files) = build_app("ui_help");
+        let total = app.keymap.get_help(&app.screen_mode).len();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let screen = |terminal: &Terminal<TestBackend>| -> String {
+            let buffer = terminal.backend().buffer();
+            buffer.content.iter().map(|c| c.symbol()).collect()
+        };
+
+        app.dispatch(Action::Help).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert!(screen(&terminal).contains(&format!("{} more", total - 22)));
+
+        for _ in 0..total {
+            app.dispatch(Action::NextItem).unwrap();
+        }
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.help_offset, total - 22);
+        assert!(screen(&terminal).contains("Undo"));
+        app.dispatch(Action::PreviousItem).unwrap();
+        assert_eq!(app.help_offset, total - 23);
     }
 }
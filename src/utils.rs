@@ -4,7 +4,7 @@ use std::{
 };
 
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -13,10 +13,15 @@ use ratatui::{
     Terminal,
 };
 
-pub fn init_terminal<B: Backend>() -> Result<Terminal<CrosstermBackend<Stderr>>, Box<dyn Error>> {
+pub fn init_terminal() -> Result<Terminal<CrosstermBackend<Stderr>>, Box<dyn Error>> {
     enable_raw_mode()?;
     let mut stderr = io::stderr();
-    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stderr,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     let backend = CrosstermBackend::new(stderr);
     let terminal = Terminal::new(backend)?;
@@ -32,7 +37,8 @@ pub fn cleanup_terminal<B: Backend + Write>(
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     Ok(())
 }
@@ -0,0 +1,475 @@
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::comps::{CursorDirection, CursorManager};
+
+// Vi stuff
+//
+// Optional modal editing for the text prompts (`vi_mode = true` in the
+// config). Prompts open in insert state, `Esc` switches to normal state and
+// a second `Esc` cancels the prompt. Normal state understands the motions
+// `h l w b e 0 $ f<c>`, the operators `d c y` (doubled for the whole line),
+// `x i a I A p P` and `.` to repeat the last change. Yanked and deleted text
+// shares the readline yank buffer.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViState {
+    Insert,
+    Normal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViKey {
+    Char(char),
+    Backspace,
+    Escape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    WordStart,
+    WordBack,
+    WordEnd,
+    LineStart,
+    LineEnd,
+    // After the last character, only used by `A`.
+    End,
+    Find(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Move(Motion),
+    // `None` applies the operator to the whole line (`dd`, `cc`, `yy`).
+    Apply(Operator, Option<Motion>),
+    // Enters insert state after `motion`, if any.
+    Insert(Option<Motion>),
+    DeleteChar,
+    Put { before: bool },
+    Repeat,
+}
+
+enum Parse<T> {
+    Done(T),
+    Pending,
+    Invalid,
+}
+
+pub struct Vi {
+    pub state: ViState,
+    pending: Vec<char>,
+    // Keys of the change being made, until it is back in normal state.
+    recording: Option<Vec<ViKey>>,
+    last_change: Vec<ViKey>,
+}
+
+impl Vi {
+    pub fn new() -> Self {
+        Vi {
+            state: ViState::Insert,
+            pending: vec![],
+            recording: None,
+            last_change: vec![],
+        }
+    }
+
+    // Every prompt opens in insert state.
+    pub fn reset(&mut self) {
+        if let Some(keys) = self.recording.take() {
+            self.last_change = keys;
+        }
+        self.state = ViState::Insert;
+        self.pending.clear();
+    }
+
+    pub fn handle_char(&mut self, c: char, cursor_manager: &mut CursorManager) {
+        self.handle_key(ViKey::Char(c), cursor_manager);
+    }
+
+    pub fn handle_backspace(&mut self, cursor_manager: &mut CursorManager) {
+        self.handle_key(ViKey::Backspace, cursor_manager);
+    }
+
+    // Returns false when there is nothing to leave, the prompt should then
+    // be cancelled.
+    pub fn handle_escape(&mut self, cursor_manager: &mut CursorManager) -> bool {
+        if self.state == ViState::Normal && self.pending.is_empty() {
+            return false;
+        }
+        self.handle_key(ViKey::Escape, cursor_manager);
+        true
+    }
+
+    fn handle_key(&mut self, key: ViKey, cursor_manager: &mut CursorManager) {
+        match (self.state, key) {
+            (ViState::Insert, _) => {
+                if let Some(keys) = &mut self.recording {
+                    keys.push(key);
+                }
+                match key {
+                    ViKey::Char(c) => cursor_manager.insert(c),
+                    ViKey::Backspace => cursor_manager.delete(),
+                    ViKey::Escape => {
+                        if let Some(keys) = self.recording.take() {
+                            self.last_change = keys;
+                        }
+                        self.state = ViState::Normal;
+                        cursor_manager.move_cursor(CursorDirection::Left);
+                    }
+                }
+            }
+            (ViState::Normal, ViKey::Char(c)) => {
+                self.pending.push(c);
+                match parse_command(&self.pending) {
+                    Parse::Done(command) => {
+                        let keys: Vec<ViKey> = self.pending.drain(..).map(ViKey::Char).collect();
+                        self.run(command, keys, cursor_manager);
+                    }
+                    Parse::Pending => {}
+                    Parse::Invalid => self.pending.clear(),
+                }
+            }
+            (ViState::Normal, ViKey::Backspace) => {
+                self.pending.clear();
+                cursor_manager.move_cursor(CursorDirection::Left);
+            }
+            (ViState::Normal, ViKey::Escape) => self.pending.clear(),
+        }
+    }
+
+    fn run(&mut self, command: Command, keys: Vec<ViKey>, cursor_manager: &mut CursorManager) {
+        match command {
+            Command::Move(motion) => {
+                if let Some((target, _)) = get_target(cursor_manager, motion) {
+                    cursor_manager.cursor = target;
+                }
+            }
+            Command::Apply(operator, motion) => {
+                let range = match motion {
+                    // `cw` on a word changes up to its end, like `ce`.
+                    Some(Motion::WordStart)
+                        if operator == Operator::Change && !is_on_space(cursor_manager) =>
+                    {
+                        get_range(cursor_manager, Motion::WordEnd)
+                    }
+                    Some(motion) => get_range(cursor_manager, motion),
+                    None => Some(0..cursor_manager.string.len()),
+                };
+                let Some(range) = range else { return };
+                match operator {
+                    Operator::Delete => {
+                        cursor_manager.cut(range);
+                        self.last_change = keys;
+                    }
+                    Operator::Change => {
+                        cursor_manager.cut(range);
+                        self.start_insert(keys);
+                        return;
+                    }
+                    Operator::Yank => {
+                        cursor_manager.yank_buffer =
+                            cursor_manager.string[range.clone()].to_string();
+                        cursor_manager.cursor = range.start;
+                    }
+                }
+            }
+            Command::Insert(motion) => {
+                if let Some((target, _)) = motion.and_then(|m| get_target(cursor_manager, m)) {
+                    cursor_manager.cursor = target;
+                }
+                self.start_insert(keys);
+                return;
+            }
+            Command::DeleteChar => {
+                if let Some(range) = get_range(cursor_manager, Motion::Right) {
+                    cursor_manager.cut(range);
+                }
+                self.last_change = keys;
+            }
+            Command::Put { before } => {
+                if !before {
+                    cursor_manager.move_cursor(CursorDirection::Right);
+                }
+                cursor_manager.yank();
+                cursor_manager.move_cursor(CursorDirection::Left);
+                self.last_change = keys;
+            }
+            Command::Repeat => {
+                let change = self.last_change.clone();
+                for key in change.iter() {
+                    self.handle_key(*key, cursor_manager);
+                }
+                // A repeated change that leaves insert state open is closed
+                // right away, as it would have been when it was recorded.
+                if self.state == ViState::Insert {
+                    self.handle_key(ViKey::Escape, cursor_manager);
+                }
+                self.last_change = change;
+                return;
+            }
+        }
+        clamp_cursor(cursor_manager);
+    }
+
+    fn start_insert(&mut self, keys: Vec<ViKey>) {
+        self.recording = Some(keys);
+        self.state = ViState::Insert;
+    }
+}
+
+fn parse_motion(keys: &[char]) -> Parse<Motion> {
+    let motion = match keys {
+        ['h'] => Motion::Left,
+        ['l'] => Motion::Right,
+        ['w'] => Motion::WordStart,
+        ['b'] => Motion::WordBack,
+        ['e'] => Motion::WordEnd,
+        ['0'] => Motion::LineStart,
+        ['$'] => Motion::LineEnd,
+        ['f'] => return Parse::Pending,
+        ['f', c] => Motion::Find(*c),
+        _ => return Parse::Invalid,
+    };
+    Parse::Done(motion)
+}
+
+fn parse_command(keys: &[char]) -> Parse<Command> {
+    let command = match keys {
+        ['i'] => Command::Insert(None),
+        ['a'] => Command::Insert(Some(Motion::Right)),
+        ['I'] => Command::Insert(Some(Motion::LineStart)),
+        ['A'] => Command::Insert(Some(Motion::End)),
+        ['x'] => Command::DeleteChar,
+        ['p'] => Command::Put { before: false },
+        ['P'] => Command::Put { before: true },
+        ['.'] => Command::Repeat,
+        [op @ ('d' | 'c' | 'y'), rest @ ..] => {
+            let operator = match op {
+                'd' => Operator::Delete,
+                'c' => Operator::Change,
+                _ => Operator::Yank,
+            };
+            match rest {
+                [] => return Parse::Pending,
+                [c] if c == op => Command::Apply(operator, None),
+                _ => match parse_motion(rest) {
+                    Parse::Done(motion) => Command::Apply(operator, Some(motion)),
+                    Parse::Pending => return Parse::Pending,
+                    Parse::Invalid => return Parse::Invalid,
+                },
+            }
+        }
+        _ => match parse_motion(keys) {
+            Parse::Done(motion) => Command::Move(motion),
+            Parse::Pending => return Parse::Pending,
+            Parse::Invalid => return Parse::Invalid,
+        },
+    };
+    Parse::Done(command)
+}
+
+// Character classes for word motions: blanks, word characters and the rest.
+fn get_class(grapheme: &str) -> u8 {
+    match grapheme.chars().next() {
+        Some(c) if c.is_whitespace() => 0,
+        Some(c) if c.is_alphanumeric() || c == '_' => 1,
+        _ => 2,
+    }
+}
+
+fn is_on_space(cursor_manager: &CursorManager) -> bool {
+    cursor_manager.string[cursor_manager.cursor..]
+        .graphemes(true)
+        .next()
+        .is_none_or(|g| get_class(g) == 0)
+}
+
+// Byte offset of the grapheme reached by `motion`, and whether an operator
+// includes that grapheme. `None` when the motion fails (`f` without match).
+fn get_target(cursor_manager: &CursorManager, motion: Motion) -> Option<(usize, bool)> {
+    let graphemes: Vec<(usize, &str)> = cursor_manager.string.grapheme_indices(true).collect();
+    let n = graphemes.len();
+    let class = |j: usize| get_class(graphemes[j].1);
+    let i = graphemes
+        .iter()
+        .position(|(start, _)| *start >= cursor_manager.cursor)
+        .unwrap_or(n);
+
+    let (j, inclusive) = match motion {
+        Motion::Left => (i.saturating_sub(1), false),
+        Motion::Right => ((i + 1).min(n), false),
+        Motion::WordStart => {
+            let mut j = i;
+            if j < n && class(j) != 0 {
+                let c = class(j);
+                while j < n && class(j) == c {
+                    j += 1;
+                }
+            }
+            while j < n && class(j) == 0 {
+                j += 1;
+            }
+            (j, false)
+        }
+        Motion::WordBack => {
+            let mut j = i;
+            while j > 0 && class(j - 1) == 0 {
+                j -= 1;
+            }
+            if j > 0 {
+                let c = class(j - 1);
+                while j > 0 && class(j - 1) == c {
+                    j -= 1;
+                }
+            }
+            (j, false)
+        }
+        Motion::WordEnd => {
+            let mut j = i + 1;
+            while j < n && class(j) == 0 {
+                j += 1;
+            }
+            if j >= n {
+                (n.checked_sub(1)?, true)
+            } else {
+                let c = class(j);
+                while j + 1 < n && class(j + 1) == c {
+                    j += 1;
+                }
+                (j, true)
+            }
+        }
+        Motion::LineStart => (0, false),
+        Motion::LineEnd => (n.checked_sub(1)?, true),
+        Motion::End => (n, false),
+        Motion::Find(c) => ((i + 1..n).find(|j| graphemes[*j].1.starts_with(c))?, true),
+    };
+    let start = graphemes
+        .get(j)
+        .map_or(cursor_manager.string.len(), |g| g.0);
+    Some((start, inclusive))
+}
+
+// Bytes covered by an operator applied with `motion`.
+fn get_range(cursor_manager: &CursorManager, motion: Motion) -> Option<Range<usize>> {
+    let (target, inclusive) = get_target(cursor_manager, motion)?;
+    let cursor = cursor_manager.cursor;
+    let range = if target >= cursor {
+        let end = if inclusive {
+            cursor_manager.string[target..]
+                .graphemes(true)
+                .next()
+                .map_or(target, |g| target + g.len())
+        } else {
+            target
+        };
+        cursor..end
+    } else {
+        target..cursor
+    };
+    (!range.is_empty()).then_some(range)
+}
+
+// In normal state the cursor sits on a character, never after the last one.
+fn clamp_cursor(cursor_manager: &mut CursorManager) {
+    if cursor_manager.cursor == cursor_manager.string.len() {
+        cursor_manager.move_cursor(CursorDirection::Left);
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod vi_test {
+    use super::*;
+
+    fn type_keys(vi: &mut Vi, cursor_manager: &mut CursorManager, keys: &str) {
+        for c in keys.chars() {
+            match c {
+                '\u{1b}' => {
+                    vi.handle_escape(cursor_manager);
+                }
+                c => vi.handle_char(c, cursor_manager),
+            }
+        }
+    }
+
+    fn build(text: &str) -> (Vi, CursorManager) {
+        let mut vi = Vi::new();
+        let mut cursor_manager = CursorManager::new();
+        type_keys(&mut vi, &mut cursor_manager, text);
+        type_keys(&mut vi, &mut cursor_manager, "\u{1b}0");
+        (vi, cursor_manager)
+    }
+
+    #[test]
+    fn motions() {
+        let (mut vi, mut cursor_manager) = build("buy some_milk, now");
+
+        type_keys(&mut vi, &mut cursor_manager, "w");
+        assert_eq!(cursor_manager.cursor, 4);
+        type_keys(&mut vi, &mut cursor_manager, "e");
+        assert_eq!(cursor_manager.cursor, 12);
+        type_keys(&mut vi, &mut cursor_manager, "w");
+        assert_eq!(cursor_manager.cursor, 13);
+        type_keys(&mut vi, &mut cursor_manager, "b");
+        assert_eq!(cursor_manager.cursor, 4);
+        type_keys(&mut vi, &mut cursor_manager, "$");
+        assert_eq!(cursor_manager.cursor, 17);
+        type_keys(&mut vi, &mut cursor_manager, "0f,");
+        assert_eq!(cursor_manager.cursor, 13);
+        type_keys(&mut vi, &mut cursor_manager, "fz");
+        assert_eq!(cursor_manager.cursor, 13);
+    }
+
+    #[test]
+    fn operators() {
+        let (mut vi, mut cursor_manager) = build("buy some milk");
+
+        type_keys(&mut vi, &mut cursor_manager, "dw");
+        assert_eq!(cursor_manager.string, "some milk");
+        type_keys(&mut vi, &mut cursor_manager, "cwfresh\u{1b}");
+        assert_eq!(cursor_manager.string, "fresh milk");
+        assert_eq!(vi.state, ViState::Normal);
+        type_keys(&mut vi, &mut cursor_manager, "0yeA \u{1b}p");
+        assert_eq!(cursor_manager.string, "fresh milk fresh");
+        type_keys(&mut vi, &mut cursor_manager, "0df ");
+        assert_eq!(cursor_manager.string, "milk fresh");
+        type_keys(&mut vi, &mut cursor_manager, "x$x");
+        assert_eq!(cursor_manager.string, "ilk fres");
+        type_keys(&mut vi, &mut cursor_manager, "cc\u{1b}");
+        assert!(cursor_manager.string.is_empty());
+    }
+
+    #[test]
+    fn repeat() {
+        let (mut vi, mut cursor_manager) = build("one two three four");
+
+        type_keys(&mut vi, &mut cursor_manager, "dw.");
+        assert_eq!(cursor_manager.string, "three four");
+        type_keys(&mut vi, &mut cursor_manager, "cwsix\u{1b}w.");
+        assert_eq!(cursor_manager.string, "six six");
+        type_keys(&mut vi, &mut cursor_manager, "A!\u{1b}.");
+        assert_eq!(cursor_manager.string, "six six!!");
+    }
+
+    #[test]
+    fn escape() {
+        let (mut vi, mut cursor_manager) = build("milk");
+
+        vi.handle_char('d', &mut cursor_manager);
+        assert!(vi.handle_escape(&mut cursor_manager));
+        assert!(!vi.handle_escape(&mut cursor_manager));
+        vi.reset();
+        assert_eq!(vi.state, ViState::Insert);
+    }
+}